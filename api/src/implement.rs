@@ -27,6 +27,63 @@ impl AkroApi for TClient {
         self.call_api_at(at, "stake_weight", &account)
     }
 
+    fn authored_blocks(&self, at: &BlockId, account: AccountId) -> Result<(u32, u32)> {
+        self.call_api_at(at, "authored_blocks", &account)
+    }
+
+    fn intentions(&self, at: &BlockId) -> Result<Vec<(AccountId, IntentionProfs)>> {
+        self.call_api_at(at, "staking_intentions", &())
+    }
+
+    fn intention(&self, at: &BlockId, account: AccountId) -> Result<Option<IntentionProfs>> {
+        self.call_api_at(at, "staking_intention", &account)
+    }
+
+    fn nomination_records(
+        &self,
+        at: &BlockId,
+        nominator: AccountId,
+    ) -> Result<Vec<(AccountId, NominationRecord, Balance)>> {
+        self.call_api_at(at, "staking_nomination_records", &nominator)
+    }
+
+    fn pending_dividend(
+        &self,
+        at: &BlockId,
+        nominator: AccountId,
+        intention: AccountId,
+    ) -> Result<Balance> {
+        self.call_api_at(at, "staking_pending_dividend", &(nominator, intention))
+    }
+
+    fn certs(&self, at: &BlockId) -> Result<Vec<CertProfs>> {
+        self.call_api_at(at, "staking_certs", &())
+    }
+
+    fn unbonding(&self, at: &BlockId, account: AccountId) -> Result<Vec<(BlockNumber, Balance)>> {
+        self.call_api_at(at, "staking_unbonding", &account)
+    }
+
+    fn era_info(&self, at: &BlockId) -> Result<EraInfo> {
+        self.call_api_at(at, "staking_era_info", &())
+    }
+
+    fn staking_tokens(&self, at: &BlockId) -> Result<Vec<(Symbol, VirtualIntentionProfs, u128)>> {
+        self.call_api_at(at, "tokenstaking_tokens", &())
+    }
+
+    fn hodling_records(
+        &self,
+        at: &BlockId,
+        account: AccountId,
+    ) -> Result<Vec<(Symbol, HodlingRecord, u128, Balance)>> {
+        self.call_api_at(at, "tokenstaking_hodling_records", &account)
+    }
+
+    fn session_stakes(&self, at: &BlockId) -> Result<Vec<(Symbol, SessionStake)>> {
+        self.call_api_at(at, "tokenstaking_session_stakes", &())
+    }
+
     fn random_seed(&self, at: &BlockId) -> Result<Hash> {
         self.call_api_at(at, "random_seed", &())
     }
@@ -43,6 +100,30 @@ impl AkroApi for TClient {
         self.call_api_at(at, "lookup_address", &address)
     }
 
+    fn death_account(&self, at: &BlockId) -> Result<AccountId> {
+        self.call_api_at(at, "death_account", &())
+    }
+
+    fn account_balances(
+        &self,
+        at: &BlockId,
+        account: AccountId,
+    ) -> Result<(Balance, Balance, Balance, Balance)> {
+        self.call_api_at(at, "account_balances", &account)
+    }
+
+    fn token_balances(
+        &self,
+        at: &BlockId,
+        account: AccountId,
+    ) -> Result<Vec<(Symbol, TokenBalance)>> {
+        self.call_api_at(at, "token_balances", &account)
+    }
+
+    fn extrinsic_fee(&self, at: &BlockId, xt: &UncheckedExtrinsic) -> Result<Balance> {
+        self.call_api_at(at, "extrinsic_fee", xt)
+    }
+
     fn evaluate_block(&self, at: &BlockId, block: Block) -> Result<bool> {
         let res: Result<()> = self.call_api_at(at, "execute_block", &block);
         match res {
@@ -17,10 +17,11 @@ use akro_executor::NativeExecutor;
 use client::block_builder::BlockBuilder as ClientBlockBuilder;
 pub use client::error::{Error, ErrorKind, Result};
 use primitives::{
-    AccountId, Block, BlockId, BlockNumber, Hash, Index, InherentData, SessionKey, Timestamp,
-    UncheckedExtrinsic,
+    AccountId, Balance, Block, BlockId, BlockNumber, Hash, Index, InherentData, SessionKey,
+    Timestamp, UncheckedExtrinsic,
 };
 use runtime::Address;
+pub use runtime::{Symbol, TokenBalance};
 use runtime_primitives::{
     traits::{BlockNumberToHash, CurrentHeight},
     transaction_validity::TransactionValidity,
@@ -29,6 +30,21 @@ use substrate_primitives::Blake2Hasher;
 
 mod implement;
 
+/// Intention profile as stored by the staking module.
+pub type IntentionProfs = runtime::IntentionProfs<Balance, BlockNumber>;
+/// Nomination record as stored by the staking module.
+pub type NominationRecord = runtime::NominationRecord<Balance, BlockNumber>;
+/// Certificate profile as stored by the staking module.
+pub type CertProfs = runtime::CertProfs<AccountId, BlockNumber>;
+/// Staking era and session progress.
+pub type EraInfo = runtime::EraInfo<BlockNumber>;
+/// Virtual intention of a token as stored by the token-staking module.
+pub type VirtualIntentionProfs = runtime::VirtualIntentionProfs<Balance, BlockNumber>;
+/// Hodling record of a token as stored by the token-staking module.
+pub type HodlingRecord = runtime::HodlingRecord<BlockNumber>;
+/// Price and stake of a token at the last session.
+pub type SessionStake = runtime::SessionStake<Balance, BlockNumber>;
+
 /// Build new blocks.
 pub trait BlockBuilder {
     /// Push an extrinsic onto the block. Fails if the extrinsic is invalid.
@@ -61,6 +77,52 @@ pub trait AkroApi:
     /// Get a validator stake weight at a given block.
     fn stake_weight(&self, at: &BlockId, account: AccountId) -> Result<u64>;
 
+    /// Get the blocks a validator authored in the current and the previous era at a given block.
+    fn authored_blocks(&self, at: &BlockId, account: AccountId) -> Result<(u32, u32)>;
+
+    /// Get all staking intentions with their profiles at a given block.
+    fn intentions(&self, at: &BlockId) -> Result<Vec<(AccountId, IntentionProfs)>>;
+
+    /// Get the profile of an intention at a given block, `None` if not an intention.
+    fn intention(&self, at: &BlockId, account: AccountId) -> Result<Option<IntentionProfs>>;
+
+    /// Get the nomination records of a nominator with the pending dividend of each nominee.
+    fn nomination_records(
+        &self,
+        at: &BlockId,
+        nominator: AccountId,
+    ) -> Result<Vec<(AccountId, NominationRecord, Balance)>>;
+
+    /// Get the dividend a nominator would get by claiming from an intention at a given block.
+    fn pending_dividend(
+        &self,
+        at: &BlockId,
+        nominator: AccountId,
+        intention: AccountId,
+    ) -> Result<Balance>;
+
+    /// Get all issued certificates at a given block.
+    fn certs(&self, at: &BlockId) -> Result<Vec<CertProfs>>;
+
+    /// Get the stake of an account waiting to be unreserved, with the block it is unreserved at.
+    fn unbonding(&self, at: &BlockId, account: AccountId) -> Result<Vec<(BlockNumber, Balance)>>;
+
+    /// Get the staking era and session at a given block.
+    fn era_info(&self, at: &BlockId) -> Result<EraInfo>;
+
+    /// Get the staking tokens with their virtual intention and latest total weight.
+    fn staking_tokens(&self, at: &BlockId) -> Result<Vec<(Symbol, VirtualIntentionProfs, u128)>>;
+
+    /// Get the hodling records of an account with the latest weight and claimable dividend.
+    fn hodling_records(
+        &self,
+        at: &BlockId,
+        account: AccountId,
+    ) -> Result<Vec<(Symbol, HodlingRecord, u128, Balance)>>;
+
+    /// Get the price and discounted stake of every staking token at the last session.
+    fn session_stakes(&self, at: &BlockId) -> Result<Vec<(Symbol, SessionStake)>>;
+
     /// Get the value of the randomness beacon at a given block.
     fn random_seed(&self, at: &BlockId) -> Result<Hash>;
 
@@ -73,6 +135,26 @@ pub trait AkroApi:
     /// Get the account id of an address at a block.
     fn lookup(&self, at: &BlockId, address: Address) -> Result<Option<AccountId>>;
 
+    /// Get the account fees are burned to at a block, which can not send transactions.
+    fn death_account(&self, at: &BlockId) -> Result<AccountId>;
+
+    /// Get the free, reserved, staking-locked and frozen balance of an account at a block.
+    fn account_balances(
+        &self,
+        at: &BlockId,
+        account: AccountId,
+    ) -> Result<(Balance, Balance, Balance, Balance)>;
+
+    /// Get the balance of an account in each valid token at a block.
+    fn token_balances(
+        &self,
+        at: &BlockId,
+        account: AccountId,
+    ) -> Result<Vec<(Symbol, TokenBalance)>>;
+
+    /// Get the fee an extrinsic would pay at a block, for the transaction and for its call.
+    fn extrinsic_fee(&self, at: &BlockId, xt: &UncheckedExtrinsic) -> Result<Balance>;
+
     /// Evaluate a block. Returns true if the block is good, false if it is known to be bad,
     /// and an error if we can't evaluate for some reason.
     fn evaluate_block(&self, at: &BlockId, block: Block) -> Result<bool>;
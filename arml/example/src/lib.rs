@@ -40,6 +40,7 @@ use support::StorageValue;
 use arml_support::storage::linked_node::{
     LinkedNodeCollection, MultiNodeIndex, MultiNodeIndexT, Node, NodeIndex, NodeT,
 };
+use arml_support::storage::ordered_list::SkipIndexedCollection;
 
 use arml_support::storage::btree_map::CodecBTreeMap;
 
@@ -96,6 +97,7 @@ impl<T: Trait> LinkedNodeCollection for LinkedNodes<T> {
     type Header = NodeHeader<T>;
     type NodeMap = NodeMap<T>;
     type Tail = NodeTail<T>;
+    type Length = NodeLength<T>;
 }
 
 /// 2.2 create a Phantom struct and let LinkedNodeCollection impl it, notice this LinkedNodeCollection's associate type
@@ -107,6 +109,7 @@ impl<T: Trait> LinkedNodeCollection for LinkedOptionNodes<T> {
     type Header = OpNodeHeader<T>;
     type NodeMap = OpNodeMap<T>;
     type Tail = OpNodeTail<T>;
+    type Length = OpNodeLength<T>;
 }
 
 #[allow(unused)]
@@ -116,6 +119,24 @@ impl<T: Trait> LinkedNodeCollection for LinkedOptionMultiKey<T> {
     type Header = MultiHeader<T>;
     type NodeMap = OpNodeMap2<T>;
     type Tail = MultiTail<T>;
+    type Length = MultiLength<T>;
+}
+
+/// 2.3 a sorted Option node collection, `SkipIndexedCollection` is only needed for
+/// `insert_sorted_indexed`
+#[allow(unused)]
+struct SortedOrders<T: Trait>(support::storage::generator::PhantomData<T>);
+
+impl<T: Trait> LinkedNodeCollection for SortedOrders<T> {
+    type Header = SortedHeader<T>;
+    type NodeMap = SortedNodeMap<T>;
+    type Tail = SortedTail<T>;
+    type Length = SortedLength<T>;
+}
+
+impl<T: Trait> SkipIndexedCollection for SortedOrders<T> {
+    type SkipIndex = SortedSkipIndex<T>;
+    const SKIP_SPAN: u32 = 2;
 }
 
 /// 3 create Node elements,
@@ -134,6 +155,8 @@ decl_storage! {
         pub NodeTail get(node_tail): NodeIndex<Order<T::AccountId, T::Balance>>;
         /// linked node collection, must use `Node` to wrap the data struct, and the type must be `StorageMap`, the key must be the index for the data struct
         pub NodeMap get(node_map): map T::AccountId => Node<Order<T::AccountId, T::Balance>>;
+        /// linked node length, maintained by the collection, the type must be `u32` `StorageValue`
+        pub NodeLength get(node_length): u32;
 
         // Option node group
         /// linked node header, must use `NodeIndex` to wrap the data struct, and the type must be `StorageValue`, must be wrapped by Option
@@ -143,10 +166,22 @@ decl_storage! {
         /// linked node collection, must use `Node` to wrap the data struct, and the type must be `StorageMap`, the key must be the index for the data struct,
         /// must be wrapped by Option
         pub OpNodeMap get(op_node_map): map T::AccountId => Option<Node<Order<T::AccountId, T::Balance>>>;
+        /// linked node length, maintained by the collection, the type must be `u32` `StorageValue`, not wrapped by Option
+        pub OpNodeLength get(op_node_length): u32;
 
         pub MultiHeader get(multi_header): map <MultiNodeIndex<u32, Order<T::AccountId, T::Balance>> as MultiNodeIndexT>::KeyType => Option<MultiNodeIndex<u32, Order<T::AccountId, T::Balance>>>;
         pub MultiTail get(multi_tail): map <MultiNodeIndex<u32, Order<T::AccountId, T::Balance>> as MultiNodeIndexT>::KeyType => Option<MultiNodeIndex<u32, Order<T::AccountId, T::Balance>>>;
         pub OpNodeMap2 get(op_node_map2): map T::AccountId => Option<Node<Order<T::AccountId, T::Balance>>>;
+        /// linked node length for every key, the type must be `u32` `StorageMap` from the key
+        pub MultiLength get(multi_length): map u32 => u32;
+
+        // sorted Option node group, same as the Option node group
+        pub SortedHeader get(sorted_header): Option<NodeIndex<Order<T::AccountId, T::Balance>>>;
+        pub SortedTail get(sorted_tail): Option<NodeIndex<Order<T::AccountId, T::Balance>>>;
+        pub SortedNodeMap get(sorted_node_map): map T::AccountId => Option<Node<Order<T::AccountId, T::Balance>>>;
+        pub SortedLength get(sorted_length): u32;
+        /// skip index of the sorted group, must be `Vec` of the index and the type must be `StorageValue`
+        pub SortedSkipIndex get(sorted_skip_index): Vec<T::AccountId>;
     }
 }
 
@@ -165,6 +200,7 @@ impl<T: Trait> Module<T> {
     /// 2. call `init_storage` to set it to storage, use the prev Phantom struct to fill the template
     /// 3. lookup the node by youself
     /// 4. call `add_node_before`, `add_node_after`, `remove_node` to modify the linked node collection
+    /// 5. call `iter`, `iter_rev`, `len` and `clear` to read or drop the whole collection
     #[allow(unused)]
     fn for_linkednode_example(node: NodeOrder<T>) -> Result {
         let mut n: NodeOrder<T> = Node::new(Default::default());
@@ -187,6 +223,15 @@ impl<T: Trait> Module<T> {
 
         Ok(())
     }
+
+    /// example for a sorted collection, orders with the lower `data` go first
+    #[allow(unused)]
+    fn for_ordered_list_example(order: Order<T::AccountId, T::Balance>) -> Result {
+        Node::new(order).insert_sorted::<SortedOrders<T>, _>(|a, b| a.data.cmp(&b.data))?;
+        // the lowest order
+        let _first = NodeOrder::<T>::pop_front::<SortedOrders<T>>();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -196,6 +241,7 @@ mod tests {
     use primitives::testing::{Digest, DigestItem, Header};
     use primitives::traits::BlakeTwo256;
     use primitives::BuildStorage;
+    use rstd::cmp::Ordering;
     use runtime_io::with_externalities;
     use substrate_primitives::{Blake2Hasher, H256};
 
@@ -254,6 +300,10 @@ mod tests {
         r.into()
     }
 
+    fn indexes<I: Iterator<Item = NodeOrder<Test>>>(iter: I) -> Vec<u64> {
+        iter.map(|node| node.index()).collect()
+    }
+
     #[test]
     fn test_linkednode() {
         with_externalities(&mut new_test_ext(), || {
@@ -313,6 +363,16 @@ mod tests {
             assert_eq!(NodeMap::<Test>::get(4).prev(), Some(1));
             assert_eq!(NodeMap::<Test>::get(4).next(), Some(0));
 
+            assert_eq!(NodeOrder::<Test>::len::<LinkedNodes<Test>>(), 5);
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<LinkedNodes<Test>>()),
+                [1, 4, 0, 3, 2]
+            );
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter_rev::<LinkedNodes<Test>>()),
+                [2, 3, 0, 4, 1]
+            );
+
             // remove_node
             // (1) 4 0 3 2
             let mut node1 = NodeMap::<Test>::get(1);
@@ -333,6 +393,11 @@ mod tests {
             assert_eq!(NodeMap::<Test>::get(4).next(), Some(3));
             assert_eq!(NodeTail::<Test>::get().index(), 3);
             assert_eq!(NodeMap::<Test>::get(3).prev(), Some(4));
+            assert_eq!(NodeOrder::<Test>::len::<LinkedNodes<Test>>(), 2);
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<LinkedNodes<Test>>()),
+                [4, 3]
+            );
 
             // (4) 3
             let mut node4 = NodeMap::<Test>::get(4);
@@ -346,6 +411,13 @@ mod tests {
             node3.remove_node::<LinkedNodes<Test>>().unwrap();
             assert_eq!(NodeHeader::<Test>::exists(), false);
             assert_eq!(NodeTail::<Test>::exists(), false);
+            assert_eq!(NodeOrder::<Test>::len::<LinkedNodes<Test>>(), 0);
+            assert_eq!(
+                NodeOrder::<Test>::iter::<LinkedNodes<Test>>()
+                    .next()
+                    .is_none(),
+                true
+            );
         })
     }
 
@@ -416,6 +488,16 @@ mod tests {
             assert_eq!(OpNodeMap::<Test>::get(4).unwrap().prev(), Some(1));
             assert_eq!(OpNodeMap::<Test>::get(4).unwrap().next(), Some(0));
 
+            assert_eq!(NodeOrder::<Test>::len::<LinkedOptionNodes<Test>>(), 5);
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<LinkedOptionNodes<Test>>()),
+                [1, 4, 0, 3, 2]
+            );
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter_rev::<LinkedOptionNodes<Test>>()),
+                [2, 3, 0, 4, 1]
+            );
+
             // remove_node
             // (1) 4 0 3 2
             let mut node1 = OpNodeMap::<Test>::get(1).unwrap();
@@ -459,6 +541,226 @@ mod tests {
                 .unwrap();
             assert_eq!(OpNodeHeader::<Test>::exists(), false);
             assert_eq!(OpNodeTail::<Test>::exists(), false);
+            assert_eq!(NodeOrder::<Test>::len::<LinkedOptionNodes<Test>>(), 0);
+        })
+    }
+
+    #[test]
+    fn test_linkednode_clear() {
+        with_externalities(&mut new_test_ext(), || {
+            let mut node0 = Node::new(Order { id: 0, data: 0 });
+            node0.init_storage::<LinkedOptionNodes<Test>>();
+            for i in 1..4 {
+                let tail_index = OpNodeTail::<Test>::get().unwrap().index();
+                let mut tail = OpNodeMap::<Test>::get(tail_index).unwrap();
+                tail.add_option_node_after::<LinkedOptionNodes<Test>>(Node::new(Order {
+                    id: i,
+                    data: i,
+                }))
+                .unwrap();
+            }
+            assert_eq!(NodeOrder::<Test>::len::<LinkedOptionNodes<Test>>(), 4);
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<LinkedOptionNodes<Test>>()),
+                [0, 1, 2, 3]
+            );
+
+            NodeOrder::<Test>::clear::<LinkedOptionNodes<Test>>();
+            assert_eq!(NodeOrder::<Test>::len::<LinkedOptionNodes<Test>>(), 0);
+            assert_eq!(OpNodeHeader::<Test>::exists(), false);
+            assert_eq!(OpNodeTail::<Test>::exists(), false);
+            for i in 0..4 {
+                assert_eq!(OpNodeMap::<Test>::exists(i), false);
+            }
+        })
+    }
+
+    fn by_data(a: &Order<u64, u64>, b: &Order<u64, u64>) -> Ordering {
+        a.data.cmp(&b.data)
+    }
+
+    #[test]
+    fn test_ordered_list() {
+        with_externalities(&mut new_test_ext(), || {
+            // (id, data)
+            for (id, data) in [(1, 30), (2, 10), (3, 20), (4, 10), (5, 40)].iter() {
+                Node::new(Order {
+                    id: *id,
+                    data: *data,
+                })
+                .insert_sorted::<SortedOrders<Test>, _>(by_data)
+                .unwrap();
+            }
+            // equal data keeps the insertion order
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<SortedOrders<Test>>()),
+                [2, 4, 3, 1, 5]
+            );
+            assert_eq!(NodeOrder::<Test>::len::<SortedOrders<Test>>(), 5);
+
+            // already in the list
+            assert_eq!(
+                Node::new(Order { id: 3, data: 0 })
+                    .insert_sorted::<SortedOrders<Test>, _>(by_data)
+                    .is_err(),
+                true
+            );
+
+            assert_eq!(
+                NodeOrder::<Test>::pop_front::<SortedOrders<Test>>().map(|o| o.id),
+                Some(2)
+            );
+            assert_eq!(
+                NodeOrder::<Test>::pop_back::<SortedOrders<Test>>().map(|o| o.id),
+                Some(5)
+            );
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<SortedOrders<Test>>()),
+                [4, 3, 1]
+            );
+            assert_eq!(NodeOrder::<Test>::len::<SortedOrders<Test>>(), 3);
+
+            for _ in 0..3 {
+                NodeOrder::<Test>::pop_front::<SortedOrders<Test>>().unwrap();
+            }
+            assert_eq!(
+                NodeOrder::<Test>::pop_front::<SortedOrders<Test>>().is_none(),
+                true
+            );
+            assert_eq!(SortedHeader::<Test>::exists(), false);
+            assert_eq!(SortedTail::<Test>::exists(), false);
+        })
+    }
+
+    #[test]
+    fn test_ordered_list_skip_index() {
+        with_externalities(&mut new_test_ext(), || {
+            // ascending inserts walk the whole list, every few become a checkpoint
+            for id in 0..10 {
+                Node::new(Order { id, data: id * 10 })
+                    .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                    .unwrap();
+            }
+            assert_eq!(Module::<Test>::sorted_skip_index().is_empty(), false);
+
+            // goes in between, starting from a checkpoint
+            Node::new(Order { id: 10, data: 55 })
+                .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                .unwrap();
+            Node::new(Order { id: 11, data: 0 })
+                .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                .unwrap();
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter::<SortedOrders<Test>>()),
+                [0, 11, 1, 2, 3, 4, 5, 10, 6, 7, 8, 9]
+            );
+
+            // checkpoints stay in list order
+            let order: Vec<u64> = indexes(NodeOrder::<Test>::iter::<SortedOrders<Test>>());
+            let positions: Vec<usize> = Module::<Test>::sorted_skip_index()
+                .iter()
+                .map(|i| order.iter().position(|o| o == i).unwrap())
+                .collect();
+            let mut sorted = positions.clone();
+            sorted.sort();
+            assert_eq!(positions, sorted);
+
+            // a removed checkpoint is dropped with its node
+            let checkpoint = Module::<Test>::sorted_skip_index()[0];
+            let mut node = SortedNodeMap::<Test>::get(checkpoint).unwrap();
+            node.remove_indexed::<SortedOrders<Test>>().unwrap();
+            assert_eq!(
+                Module::<Test>::sorted_skip_index().contains(&checkpoint),
+                false
+            );
+            Node::new(Order { id: 12, data: 95 })
+                .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                .unwrap();
+            assert_eq!(
+                NodeOrder::<Test>::iter_rev::<SortedOrders<Test>>()
+                    .next()
+                    .map(|n| n.index()),
+                Some(12)
+            );
+
+            let front = NodeOrder::<Test>::iter::<SortedOrders<Test>>()
+                .next()
+                .unwrap()
+                .index();
+            assert_eq!(
+                NodeOrder::<Test>::pop_front_indexed::<SortedOrders<Test>>().map(|o| o.id),
+                Some(front)
+            );
+            assert_eq!(
+                NodeOrder::<Test>::pop_back_indexed::<SortedOrders<Test>>().map(|o| o.id),
+                Some(12)
+            );
+            for i in Module::<Test>::sorted_skip_index() {
+                assert!(SortedNodeMap::<Test>::exists(i));
+            }
+        })
+    }
+
+    #[test]
+    fn test_ordered_list_skip_index_reinsert() {
+        with_externalities(&mut new_test_ext(), || {
+            for id in 0..10 {
+                Node::new(Order { id, data: id * 10 })
+                    .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                    .unwrap();
+            }
+            let checkpoint = Module::<Test>::sorted_skip_index()[0];
+            let mut node = SortedNodeMap::<Test>::get(checkpoint).unwrap();
+            node.remove_indexed::<SortedOrders<Test>>().unwrap();
+
+            // the same index goes back in at the end of the list, its old checkpoint must not
+            // come back in the middle of the skip index
+            Node::new(Order {
+                id: checkpoint,
+                data: 1_000,
+            })
+            .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+            .unwrap();
+            Node::new(Order { id: 20, data: 5 })
+                .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                .unwrap();
+
+            let order: Vec<u64> = indexes(NodeOrder::<Test>::iter::<SortedOrders<Test>>());
+            assert_eq!(order.last(), Some(&checkpoint));
+            assert_eq!(order[1], 20);
+            let positions: Vec<usize> = Module::<Test>::sorted_skip_index()
+                .iter()
+                .map(|i| order.iter().position(|o| o == i).unwrap())
+                .collect();
+            let mut sorted = positions.clone();
+            sorted.sort();
+            assert_eq!(positions, sorted);
+        })
+    }
+
+    #[test]
+    fn test_ordered_list_skip_index_splits_segments() {
+        with_externalities(&mut new_test_ext(), || {
+            // every insert becomes the new header, it walks past nothing
+            for id in 0..20 {
+                Node::new(Order {
+                    id,
+                    data: (20 - id) * 10,
+                })
+                .insert_sorted_indexed::<SortedOrders<Test>, _>(by_data)
+                .unwrap();
+            }
+
+            let order: Vec<u64> = indexes(NodeOrder::<Test>::iter::<SortedOrders<Test>>());
+            let mut bounds: Vec<usize> = Module::<Test>::sorted_skip_index()
+                .iter()
+                .map(|i| order.iter().position(|o| o == i).unwrap())
+                .collect();
+            bounds.insert(0, 0);
+            bounds.push(order.len());
+            for segment in bounds.windows(2) {
+                assert!(segment[1] - segment[0] <= 2 * SortedOrders::<Test>::SKIP_SPAN as usize);
+            }
         })
     }
 
@@ -489,39 +791,36 @@ mod tests {
                 .unwrap();
 
             // test key 10
-            let test_v = [2_u64, 0, 1];
-            let mut index = Module::<Test>::multi_header(10).unwrap().index();
-            let mut v = vec![];
-            loop {
-                if let Some(node) = Module::<Test>::op_node_map2(&index) {
-                    v.push(node.index());
-                    if let Some(next) = node.next() {
-                        index = next;
-                    } else {
-                        break;
-                    }
-                } else {
-                    break;
-                }
-            }
-            assert_eq!(v.as_slice(), test_v);
-
-            let test_v = [3_u64, 4];
-            let mut index = Module::<Test>::multi_header(99).unwrap().index();
-            let mut v = vec![];
-            loop {
-                if let Some(node) = Module::<Test>::op_node_map2(&index) {
-                    v.push(node.index());
-                    if let Some(next) = node.next() {
-                        index = next;
-                    } else {
-                        break;
-                    }
-                } else {
-                    break;
-                }
-            }
-            assert_eq!(v.as_slice(), test_v);
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter_withkey::<
+                    LinkedOptionMultiKey<Test>,
+                    u32,
+                >(&10)),
+                [2, 0, 1]
+            );
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter_rev_withkey::<
+                    LinkedOptionMultiKey<Test>,
+                    u32,
+                >(&10)),
+                [1, 0, 2]
+            );
+            assert_eq!(
+                NodeOrder::<Test>::len_withkey::<LinkedOptionMultiKey<Test>, u32>(&10),
+                3
+            );
+
+            assert_eq!(
+                indexes(NodeOrder::<Test>::iter_withkey::<
+                    LinkedOptionMultiKey<Test>,
+                    u32,
+                >(&99)),
+                [3, 4]
+            );
+            assert_eq!(
+                NodeOrder::<Test>::len_withkey::<LinkedOptionMultiKey<Test>, u32>(&99),
+                2
+            );
 
             let r = Module::<Test>::multi_tail(50) == None;
             assert_eq!(r, true);
@@ -558,6 +857,21 @@ mod tests {
             assert_eq!(node3.next(), None);
             assert_eq!(Module::<Test>::multi_header(99) == None, true);
             assert_eq!(Module::<Test>::multi_tail(99) == None, true);
+            assert_eq!(
+                NodeOrder::<Test>::len_withkey::<LinkedOptionMultiKey<Test>, u32>(&99),
+                0
+            );
+
+            // clear key 10, nodes of other keys are untouched
+            NodeOrder::<Test>::clear_withkey::<LinkedOptionMultiKey<Test>, u32>(&10);
+            assert_eq!(Module::<Test>::multi_header(10) == None, true);
+            assert_eq!(Module::<Test>::multi_tail(10) == None, true);
+            assert_eq!(
+                NodeOrder::<Test>::len_withkey::<LinkedOptionMultiKey<Test>, u32>(&10),
+                0
+            );
+            assert_eq!(Module::<Test>::op_node_map2(0) == None, true);
+            assert_eq!(Module::<Test>::op_node_map2(2) == None, true);
         })
     }
 }
@@ -35,7 +35,9 @@ extern crate sr_io as runtime_io;
 extern crate substrate_primitives;
 
 use associations::{ChannelRelationship, RevChannelRelationship};
-use balances::{address::Address, FreeBalance, OnDilution, ReservedBalance, TotalIssuance};
+use balances::{
+    address::Address, EnsureAccountLiquid, FreeBalance, OnDilution, ReservedBalance, TotalIssuance,
+};
 use codec::Codec;
 use primitives::{
     traits::{As, OnFinalise, One, Zero},
@@ -48,6 +50,9 @@ use session::OnSessionChange;
 use system::ensure_signed;
 
 use arml_support::storage::btree_map::CodecBTreeMap;
+use arml_support::migration::{known_accounts, Migrate};
+use arml_support::{CallFee, EnumerableStorageDoubleMap};
+use arml_system::OnBlockStart;
 
 pub mod vote_weight;
 
@@ -135,8 +140,24 @@ pub struct CertProfs<AccountId: Default, BlockNumber: Default> {
     pub remaining_shares: u32,
 }
 
+/// Era and session progress, for the runtime API.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub struct EraInfo<BlockNumber: Default> {
+    pub current_era: BlockNumber,
+    pub sessions_per_era: BlockNumber,
+    pub era_length: BlockNumber,
+    pub last_era_length_change: BlockNumber,
+    pub current_session: BlockNumber,
+    pub session_length: BlockNumber,
+}
+
 pub trait Trait:
-    balances::Trait + session::Trait + tokenbalances::Trait + associations::Trait
+    balances::Trait
+    + session::Trait
+    + tokenbalances::Trait
+    + associations::Trait
+    + arml_support::Trait
 {
     /// Some tokens minted.
     type OnRewardMinted: OnDilution<<Self as balances::Trait>::Balance>;
@@ -150,10 +171,14 @@ pub trait Trait:
 }
 
 pub trait OnNewSessionForTokenStaking<AccountId: Default + Codec, Balance> {
+    /// Update the stake of the token validators for the new session.
+    fn on_new_session();
+    /// Stake of the token validators, as of the last `on_new_session`.
     fn token_staking_info() -> Vec<(Validator<AccountId>, Balance)>;
 }
 
 impl<AccountId: Default + Codec, Balance> OnNewSessionForTokenStaking<AccountId, Balance> for () {
+    fn on_new_session() {}
     fn token_staking_info() -> Vec<(Validator<AccountId>, Balance)> {
         Vec::new()
     }
@@ -203,6 +228,22 @@ decl_module! {
     }
 }
 
+impl<T: Trait> CallFee<T::Balance> for Call<T> {
+    fn call_fee(&self) -> T::Balance {
+        match *self {
+            Call::stake(..) => Module::<T>::stake_fee(),
+            Call::unstake(..) => Module::<T>::unstake_fee(),
+            Call::register(..) => Module::<T>::register_fee(),
+            Call::activate(..) => Module::<T>::activate_fee(),
+            Call::deactivate(..) => Module::<T>::deactivate_fee(),
+            Call::claim(..) => Module::<T>::claim_fee(),
+            Call::nominate(..) => Module::<T>::nominate_fee(),
+            Call::unnominate(..) => Module::<T>::unnominate_fee(),
+            _ => Zero::zero(),
+        }
+    }
+}
+
 /// An event in this module.
 decl_event!(
     pub enum Event<T> where <T as balances::Trait>::Balance, <T as system::Trait>::AccountId {
@@ -216,11 +257,35 @@ decl_event!(
     }
 );
 
+/// Legacy layout of all nomination records of a nominator, see `NominationRecordsOf`.
 pub type Nominations<T> = CodecBTreeMap<
     <T as system::Trait>::AccountId,
     NominationRecord<<T as balances::Trait>::Balance, <T as system::Trait>::BlockNumber>,
 >;
 
+/// Nominator -> nominee -> nomination record, enumerable per nominator.
+pub struct NominationRecordsOf<T>(rstd::marker::PhantomData<T>);
+
+impl<T: Trait> EnumerableStorageDoubleMap for NominationRecordsOf<T> {
+    type Key1 = T::AccountId;
+    type Key2 = T::AccountId;
+    type Value = NominationRecord<T::Balance, T::BlockNumber>;
+
+    const PREFIX: &'static [u8] = b"Staking NominationRecordsOf";
+}
+
+/// Intention -> nominators with a non-zero nomination, the reverse index of
+/// `NominationRecordsOf`. Values are always `true`, an empty value would not be stored.
+pub struct NominatorsOf<T>(rstd::marker::PhantomData<T>);
+
+impl<T: Trait> EnumerableStorageDoubleMap for NominatorsOf<T> {
+    type Key1 = T::AccountId;
+    type Key2 = T::AccountId;
+    type Value = bool;
+
+    const PREFIX: &'static [u8] = b"Staking NominatorsOf";
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as Staking {
 
@@ -286,7 +351,8 @@ decl_storage! {
         pub IntentionProfiles get(intention_profiles): map T::AccountId => IntentionProfs<T::Balance, T::BlockNumber>;
         /// All nominator -> profiles
         pub NominatorProfiles get(nominator_profiles): map T::AccountId => NominatorProfs<T::AccountId, T::Balance>;
-        /// All nominator -> nomination records
+        /// Legacy nominator -> nomination records, moved into `NominationRecordsOf` by the v1
+        /// migration.
         pub NominationRecords get(nomination_records): map T::AccountId => Nominations<T>;
         /// All certificate owners
         pub CertOwners get(cert_owners): Vec<T::AccountId>;
@@ -300,6 +366,8 @@ decl_storage! {
         pub LockedAccountsOf get(locked_accounts_of): map T::BlockNumber => LockedAccounts<T::AccountId>;
         /// (nominator, unlock_block) => unlock_value
         pub LockedOf get(locked_of): map (T::AccountId, T::BlockNumber) => T::Balance;
+        /// nominator => unlock blocks of `LockedOf`
+        pub UnbondingOf get(unbonding_of): map T::AccountId => Vec<T::BlockNumber>;
     }
 
     add_extra_genesis {
@@ -308,7 +376,6 @@ decl_storage! {
 
         build(|storage: &mut primitives::StorageMap, config: &GenesisConfig<T>| {
             use codec::Encode;
-            use rstd::collections::btree_map::BTreeMap;
 
             let index = 0u32;
 
@@ -349,13 +416,13 @@ decl_storage! {
 
                 total_issuance += iprof.total_nomination;
 
-                let mut nominations: BTreeMap<T::AccountId, NominationRecord<T::Balance, T::BlockNumber>> = BTreeMap::new();
-                let mut record = NominationRecord::default();
+                let mut record: NominationRecord<T::Balance, T::BlockNumber> = NominationRecord::default();
                 record.nomination = iprof.total_nomination;
                 record.last_vote_weight = 0;
                 record.last_vote_weight_update = T::BlockNumber::sa(0);
-                nominations.insert(acnt.clone(), record);
-                storage.insert(GenesisConfig::<T>::hash(&<NominationRecords<T>>::key_for(acnt)).to_vec(), CodecBTreeMap(nominations).encode());
+                // keys of the double map are already hashed
+                storage.extend(<NominationRecordsOf<T>>::genesis_storage(acnt, vec![(acnt.clone(), record)]));
+                storage.extend(<NominatorsOf<T>>::genesis_storage(acnt, vec![(acnt.clone(), true)]));
 
                 let channel = name.clone();
                 let intention = acnt.clone();
@@ -370,6 +437,9 @@ decl_storage! {
             let cert_index = index + 1;
             storage.insert(GenesisConfig::<T>::hash(&<CertOwnerIndex<T>>::key()).to_vec(), cert_index.encode());
 
+            let (version_key, version) = arml_support::migration::genesis_version::<Module<T>>();
+            storage.insert(version_key, version);
+
         });
     }
 }
@@ -384,20 +454,47 @@ impl<T: Trait> Module<T> {
         nominator: &T::AccountId,
         nominee: &T::AccountId,
     ) -> NominationRecord<T::Balance, T::BlockNumber> {
-        if let Some(record) = <NominationRecords<T>>::get(nominator).0.get(nominee) {
-            return record.clone();
-        }
-        <NominationRecord<T::Balance, T::BlockNumber>>::default()
+        <NominationRecordsOf<T>>::get_or_default(nominator.clone(), nominee.clone())
     }
 
+    /// Write the nomination record and keep `NominatorsOf` in step with its nomination.
     pub fn insert_nomination_record(
         nominator: &T::AccountId,
         nominee: &T::AccountId,
         record: NominationRecord<T::Balance, T::BlockNumber>,
     ) {
-        let mut nominations = <NominationRecords<T>>::get(nominator);
-        nominations.0.insert(nominee.clone(), record);
-        <NominationRecords<T>>::insert(nominator, nominations);
+        if record.nomination.is_zero() {
+            <NominatorsOf<T>>::remove(nominee.clone(), nominator.clone());
+        } else if <NominatorsOf<T>>::exists(nominee.clone(), nominator.clone()) == false {
+            <NominatorsOf<T>>::insert(nominee.clone(), nominator.clone(), true);
+        }
+        <NominationRecordsOf<T>>::insert(nominator.clone(), nominee.clone(), record);
+    }
+
+    /// All nominees of a nominator with the nomination records.
+    pub fn nomination_records_of(
+        nominator: &T::AccountId,
+    ) -> Vec<(T::AccountId, NominationRecord<T::Balance, T::BlockNumber>)> {
+        <NominationRecordsOf<T>>::iter_prefix(nominator.clone()).collect()
+    }
+
+    /// Nominators with a non-zero nomination to the intention.
+    pub fn nominators_of(intention: &T::AccountId) -> Vec<T::AccountId> {
+        <NominatorsOf<T>>::keys(intention.clone())
+    }
+
+    /// Move the legacy `NominationRecords` of a nominator into `NominationRecordsOf`.
+    ///
+    /// Records already in `NominationRecordsOf` are newer than the legacy ones and are kept.
+    fn migrate_nomination_records(nominator: &T::AccountId) {
+        if <NominationRecords<T>>::exists(nominator) == false {
+            return;
+        }
+        for (nominee, record) in <NominationRecords<T>>::take(nominator).0 {
+            if <NominationRecordsOf<T>>::exists(nominator.clone(), nominee.clone()) == false {
+                Self::insert_nomination_record(nominator, &nominee, record);
+            }
+        }
     }
 
     pub fn total_nomination_of_intention(intention: &T::AccountId) -> T::Balance {
@@ -423,10 +520,7 @@ impl<T: Trait> Module<T> {
 
     /// Nomination of a nominator to his some nominee
     pub fn nomination_of_nominator(nominator: &T::AccountId, nominee: &T::AccountId) -> T::Balance {
-        if let Some(record) = <NominationRecords<T>>::get(nominator).0.get(nominee) {
-            return record.nomination;
-        }
-        Zero::zero()
+        Self::nomination_record_of(nominator, nominee).nomination
     }
 
     /// All funds a nominator has nominated to his nominees
@@ -438,6 +532,84 @@ impl<T: Trait> Module<T> {
             .fold(Zero::zero(), |acc: T::Balance, x| acc + x)
     }
 
+    // VIEWS, exported by the runtime API
+
+    /// All intentions with their profiles.
+    pub fn intentions_with_profile(
+    ) -> Vec<(T::AccountId, IntentionProfs<T::Balance, T::BlockNumber>)> {
+        Self::intentions()
+            .into_iter()
+            .map(|i| {
+                let iprof = Self::intention_profiles(&i);
+                (i, iprof)
+            })
+            .collect()
+    }
+
+    /// Profile of an intention, `None` if the account is not an intention.
+    pub fn intention_with_profile(
+        who: &T::AccountId,
+    ) -> Option<IntentionProfs<T::Balance, T::BlockNumber>> {
+        if Self::intentions().contains(who) {
+            Some(Self::intention_profiles(who))
+        } else {
+            None
+        }
+    }
+
+    /// Dividend the nominator would get by claiming from the intention now.
+    pub fn pending_dividend(nominator: &T::AccountId, intention: &T::AccountId) -> T::Balance {
+        let record = Self::nomination_record_of(nominator, intention);
+        let iprof = Self::intention_profiles(intention);
+        Self::generic_dividend(&record, &iprof)
+    }
+
+    /// All nomination records of a nominator with the pending dividend of each.
+    pub fn nomination_records_with_dividend(
+        nominator: &T::AccountId,
+    ) -> Vec<(
+        T::AccountId,
+        NominationRecord<T::Balance, T::BlockNumber>,
+        T::Balance,
+    )> {
+        Self::nomination_records_of(nominator)
+            .into_iter()
+            .map(|(nominee, record)| {
+                let iprof = Self::intention_profiles(&nominee);
+                let dividend = Self::generic_dividend(&record, &iprof);
+                (nominee, record, dividend)
+            })
+            .collect()
+    }
+
+    /// Stake of an account waiting to be unreserved, with the block it is unreserved at.
+    pub fn unbonding(who: &T::AccountId) -> Vec<(T::BlockNumber, T::Balance)> {
+        Self::unbonding_of(who)
+            .into_iter()
+            .map(|b| (b, Self::locked_of((who.clone(), b))))
+            .collect()
+    }
+
+    /// All issued certificates.
+    pub fn certs() -> Vec<CertProfs<T::AccountId, T::BlockNumber>> {
+        (0..Self::cert_owner_index())
+            .filter(|i| <CertProfiles<T>>::exists(i))
+            .map(|i| Self::cert_profiles(i))
+            .collect()
+    }
+
+    /// Current era and session.
+    pub fn era_info() -> EraInfo<T::BlockNumber> {
+        EraInfo {
+            current_era: Self::current_era(),
+            sessions_per_era: Self::sessions_per_era(),
+            era_length: Self::era_length(),
+            last_era_length_change: Self::last_era_length_change(),
+            current_session: <session::Module<T>>::current_index(),
+            session_length: <session::Module<T>>::length(),
+        }
+    }
+
     /// Deposit one of this module's events.
     fn deposit_event(event: Event<T>) {
         <system::Module<T>>::deposit_event(<T as Trait>::Event::from(event).into());
@@ -982,8 +1154,12 @@ impl<T: Trait> Module<T> {
         }
 
         // accumulate all balance remaining reserved of a certain account on the block
-        let locked = <LockedOf<T>>::get((who.clone(), lock_until));
-        <LockedOf<T>>::insert((who.clone(), lock_until), locked + to_lock);
+        let key = (who.clone(), lock_until);
+        if <LockedOf<T>>::exists(&key) == false {
+            <UnbondingOf<T>>::mutate(who, |blocks| blocks.push(lock_until));
+        }
+        let locked = <LockedOf<T>>::get(&key);
+        <LockedOf<T>>::insert(key, locked + to_lock);
     }
 
     /// Get the reward for the session, assuming it ends with this block.
@@ -1004,10 +1180,31 @@ impl<T: Trait> Module<T> {
         T::Balance::sa(reward / 1000)
     }
 
+    /// Index the pending unlocks of `LockedOf` in `UnbondingOf`. Locks end at most
+    /// `bonding_duration` blocks from now, unless the duration was lowered since they were made.
+    fn index_unbonding() {
+        let mut block = <system::Module<T>>::block_number();
+        let last = block + Self::bonding_duration();
+        while block <= last {
+            for who in Self::locked_accounts_of(block).to_unreserve {
+                if <LockedOf<T>>::exists(&(who.clone(), block)) == false {
+                    continue;
+                }
+                <UnbondingOf<T>>::mutate(&who, |blocks| {
+                    if !blocks.contains(&block) {
+                        blocks.push(block);
+                    }
+                });
+            }
+            block = block + One::one();
+        }
+    }
+
     /// Acutally unreserve the locked stake.
     fn unreserve(block_number: T::BlockNumber, to_unreserve: Vec<T::AccountId>) {
         for acnt in to_unreserve.into_iter() {
             let to_unlock = <LockedOf<T>>::take((acnt.clone(), block_number));
+            <UnbondingOf<T>>::mutate(&acnt, |blocks| blocks.retain(|b| *b != block_number));
             <balances::Module<T>>::unreserve(&acnt, to_unlock);
             let mut nprof = <NominatorProfiles<T>>::get(&acnt);
             nprof.locked -= to_unlock;
@@ -1035,6 +1232,8 @@ impl<T: Trait> Module<T> {
         Self::unreserve(block_number, to_unreserve);
         Self::unfreeze(to_unfreeze);
 
+        T::OnNewSessionForTokenStaking::on_new_session();
+
         if should_reward {
             // apply good session reward
             let reward = Self::this_session_reward();
@@ -1093,6 +1292,7 @@ impl<T: Trait> Module<T> {
     fn new_era() {
         // Increment current era.
         <CurrentEra<T>>::put(&(<CurrentEra<T>>::get() + One::one()));
+        <arml_system::Module<T>>::rotate_authored_blocks();
 
         // Enact era length change.
         if let Some(next_spe) = Self::next_sessions_per_era() {
@@ -1167,14 +1367,41 @@ impl<T: Trait> OnFinalise<T::BlockNumber> for Module<T> {
     fn on_finalise(_n: T::BlockNumber) {}
 }
 
+impl<T: Trait> OnBlockStart for Module<T> {
+    fn on_block_start() {
+        Self::on_runtime_upgrade();
+    }
+}
+
+impl<T: Trait> Migrate for Module<T> {
+    const VERSION_KEY: &'static [u8] = b"Staking StorageVersion";
+    const STORAGE_VERSION: u32 = 2;
+
+    fn migrate_step(from: u32) {
+        match from {
+            // v1: nomination records per (nominator, nominee), look the legacy records up for
+            // all accounts
+            0 => {
+                for who in known_accounts::<T>() {
+                    Self::migrate_nomination_records(&who);
+                }
+            }
+            // v2: unlock blocks of every account in `UnbondingOf`
+            1 => Self::index_unbonding(),
+            _ => {}
+        }
+    }
+}
+
 impl<T: Trait> OnSessionChange<T::Moment> for Module<T> {
     fn on_session_change(elapsed: T::Moment, should_reward: bool) {
         Self::new_session(elapsed, should_reward);
     }
 }
 
-impl<T: Trait> balances::EnsureAccountLiquid<T::AccountId> for Module<T> {
+impl<T: Trait> EnsureAccountLiquid<T::AccountId> for Module<T> {
     fn ensure_account_liquid(who: &T::AccountId) -> Result {
+        <arml_support::Module<T>>::ensure_not_death_account(who)?;
         if !<balances::Module<T>>::free_balance(who).is_zero() {
             Ok(())
         } else {
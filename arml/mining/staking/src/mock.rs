@@ -71,8 +71,12 @@ impl timestamp::Trait for Test {
     const TIMESTAMP_SET_POSITION: u32 = 0;
     type Moment = u64;
 }
-impl arml_support::Trait for Test {}
-impl arml_system::Trait for Test {}
+impl arml_support::Trait for Test {
+    type Event = ();
+}
+impl arml_system::Trait for Test {
+    type OnBlockStart = Staking;
+}
 impl arml_associations::Trait for Test {
     type OnCalcFee = arml_support::Module<Test>;
     type Event = ();
@@ -245,3 +245,261 @@ fn nominate_and_claim_should_work() {
         assert_eq!(Balances::free_balance(&10), 420);
     });
 }
+
+#[test]
+fn nomination_records_should_be_per_pair() {
+    with_externalities(&mut new_test_ext(0, 1, 1, 0, true, 10), || {
+        Balances::set_free_balance(&20, 1000);
+
+        assert_eq!(Staking::nominators_of(&10), [10]);
+        assert_eq!(NominationRecordsOf::<Test>::keys(10), [10]);
+
+        System::set_block_number(1);
+        assert_ok!(Staking::nominate(Origin::signed(20), 10.into(), 100));
+        assert_eq!(Staking::nominators_of(&10), [10, 20]);
+        assert_eq!(Staking::nomination_record_of(&20, &10).nomination, 100);
+        assert_eq!(Staking::nomination_records_of(&20).len(), 1);
+
+        // nominating again does not duplicate the reverse index
+        assert_ok!(Staking::nominate(Origin::signed(20), 10.into(), 100));
+        assert_eq!(Staking::nominators_of(&10), [10, 20]);
+        assert_eq!(
+            NominationRecordsOf::<Test>::get(20, 10).unwrap().nomination,
+            200
+        );
+
+        // only a full unnominate drops the nominator from the reverse index
+        assert_ok!(Staking::unnominate(Origin::signed(20), 10.into(), 150));
+        assert_eq!(Staking::nominators_of(&10), [10, 20]);
+        assert_ok!(Staking::unnominate(Origin::signed(20), 10.into(), 50));
+        assert_eq!(Staking::nominators_of(&10), [10]);
+        assert_eq!(NominatorsOf::<Test>::count(10), 1);
+    });
+}
+
+#[test]
+fn legacy_nomination_records_should_migrate() {
+    with_externalities(&mut new_test_ext(0, 1, 1, 0, true, 10), || {
+        use arml_support::migration::testing::{assert_migrated, set_storage_version};
+        use arml_support::storage::btree_map::CodecBTreeMap;
+        use std::collections::BTreeMap;
+
+        Balances::set_free_balance(&20, 1000);
+
+        let mut legacy = BTreeMap::new();
+        legacy.insert(
+            10,
+            NominationRecord {
+                nomination: 50,
+                last_vote_weight: 0,
+                last_vote_weight_update: 0,
+            },
+        );
+        <NominationRecords<Test>>::insert(&20, CodecBTreeMap(legacy));
+
+        // account 20 is known to balances, the migration moves its records into per-pair
+        // storage before anything reads them
+        set_storage_version::<Staking>(0);
+        assert_migrated::<Staking>();
+        assert_eq!(<NominationRecords<Test>>::exists(&20), false);
+        assert_eq!(Staking::nomination_record_of(&20, &10).nomination, 50);
+        assert_eq!(Staking::nomination_records_of(&20).len(), 1);
+        assert_eq!(Staking::nominators_of(&10), [10, 20]);
+
+        System::set_block_number(1);
+        assert_ok!(Staking::nominate(Origin::signed(20), 10.into(), 100));
+        assert_eq!(
+            NominationRecordsOf::<Test>::get(20, 10).unwrap().nomination,
+            150
+        );
+        assert_eq!(Staking::nominators_of(&10), [10, 20]);
+    });
+}
+
+#[test]
+fn storage_migration_should_work() {
+    with_externalities(&mut new_test_ext(0, 1, 1, 0, true, 10), || {
+        use arml_support::migration::testing::set_storage_version;
+        use arml_support::migration::Migrate;
+        use arml_support::storage::btree_map::CodecBTreeMap;
+        use runtime_support::storage;
+        use std::collections::BTreeMap;
+
+        // genesis is written with the current layout
+        assert_eq!(Staking::storage_version(), 2);
+
+        let mut legacy = BTreeMap::new();
+        legacy.insert(
+            30,
+            NominationRecord {
+                nomination: 5,
+                last_vote_weight: 0,
+                last_vote_weight_update: 0,
+            },
+        );
+        storage::put(
+            &<NominationRecords<Test>>::key_for(&10),
+            &CodecBTreeMap(legacy),
+        );
+        set_storage_version::<Staking>(0);
+
+        // the block producer inherent migrates before any signed extrinsic is dispatched
+        assert_ok!(arml_system::Module::<Test>::set_block_producer(
+            system::RawOrigin::Inherent.into(),
+            10
+        ));
+        assert_eq!(Staking::storage_version(), 2);
+        assert_eq!(<NominationRecords<Test>>::exists(&10), false);
+        assert_eq!(
+            NominationRecordsOf::<Test>::get(10, 30).unwrap().nomination,
+            5
+        );
+        assert_eq!(Staking::nominators_of(&30), [10]);
+        // the genesis record is kept
+        assert_eq!(
+            NominationRecordsOf::<Test>::get(10, 10).unwrap().nomination,
+            100_000_000
+        );
+    });
+}
+
+#[test]
+fn authored_blocks_should_rotate_with_era() {
+    with_externalities(&mut new_test_ext(0, 1, 2, 0, true, 10), || {
+        let produce = |who| {
+            assert_ok!(arml_system::Module::<Test>::set_block_producer(
+                system::RawOrigin::Inherent.into(),
+                who
+            ));
+        };
+        produce(10);
+        produce(10);
+
+        // new session, same era
+        System::set_block_number(1);
+        Session::check_rotate_session(System::block_number());
+        assert_eq!(Staking::current_era(), 0);
+        assert_eq!(arml_system::Module::<Test>::authored_blocks_of(&10), (2, 0));
+
+        // new era
+        System::set_block_number(2);
+        Session::check_rotate_session(System::block_number());
+        assert_eq!(Staking::current_era(), 1);
+        assert_eq!(arml_system::Module::<Test>::authored_blocks_of(&10), (0, 2));
+    });
+}
+
+#[test]
+fn staking_views_should_work() {
+    with_externalities(&mut new_test_ext(0, 1, 1, 0, true, 10), || {
+        Balances::set_free_balance(&20, 100_000_000);
+
+        System::set_block_number(1);
+        assert_ok!(Staking::nominate(
+            Origin::signed(20),
+            10.into(),
+            100_000_000
+        ));
+        Session::check_rotate_session(System::block_number());
+
+        let intentions = Staking::intentions_with_profile();
+        assert_eq!(intentions.len(), 1);
+        assert_eq!(intentions[0].0, 10);
+        assert_eq!(intentions[0].1.jackpot, 180);
+        assert_eq!(
+            Staking::intention_with_profile(&10),
+            Some(intentions[0].1.clone())
+        );
+        assert_eq!(Staking::intention_with_profile(&20), None);
+
+        assert_eq!(Staking::certs().len(), 1);
+        assert_eq!(Staking::certs()[0].owner, 10);
+
+        let era = Staking::era_info();
+        assert_eq!(era.current_era, 1);
+        assert_eq!(era.current_session, 1);
+        assert_eq!(era.era_length, 1);
+
+        System::set_block_number(2);
+        Session::check_rotate_session(System::block_number());
+
+        // the pending dividend is what a claim pays
+        let dividend = Staking::pending_dividend(&20, &10);
+        let records = Staking::nomination_records_with_dividend(&20);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, 10);
+        assert_eq!(records[0].1.nomination, 100_000_000);
+        assert_eq!(records[0].2, dividend);
+
+        let before = Balances::free_balance(&20);
+        assert_ok!(Staking::claim(Origin::signed(20), 10.into()));
+        assert_eq!(Balances::free_balance(&20), before + dividend);
+        assert_eq!(Staking::pending_dividend(&20, &10), 0);
+    });
+}
+
+#[test]
+fn unbonding_should_be_indexed() {
+    with_externalities(&mut new_test_ext(0, 1, 1, 0, true, 10), || {
+        Balances::set_free_balance(&20, 100_000_000);
+
+        System::set_block_number(1);
+        assert_ok!(Staking::nominate(
+            Origin::signed(20),
+            10.into(),
+            100_000_000
+        ));
+        assert_ok!(Staking::unnominate(
+            Origin::signed(20),
+            10.into(),
+            30_000_000
+        ));
+        assert_ok!(Staking::unnominate(
+            Origin::signed(20),
+            10.into(),
+            10_000_000
+        ));
+        assert_eq!(Staking::unbonding(&20), vec![(2, 40_000_000)]);
+
+        // unreserved once the bonding duration is over
+        System::set_block_number(2);
+        Session::check_rotate_session(System::block_number());
+        assert_eq!(Staking::unbonding(&20), vec![]);
+        assert_eq!(Staking::unbonding_of(&20), vec![]);
+    });
+}
+
+#[test]
+fn unbonding_should_be_indexed_by_migration() {
+    with_externalities(&mut new_test_ext(0, 1, 1, 0, true, 10), || {
+        use arml_support::migration::testing::{assert_migrated, set_storage_version};
+
+        Balances::set_free_balance(&20, 100_000_000);
+
+        System::set_block_number(1);
+        assert_ok!(Staking::nominate(
+            Origin::signed(20),
+            10.into(),
+            100_000_000
+        ));
+        assert_ok!(Staking::unnominate(
+            Origin::signed(20),
+            10.into(),
+            30_000_000
+        ));
+
+        // locks made before the index existed
+        <UnbondingOf<Test>>::remove(&20);
+        assert_eq!(Staking::unbonding(&20), vec![]);
+
+        set_storage_version::<Staking>(1);
+        assert_migrated::<Staking>();
+        assert_eq!(Staking::unbonding_of(&20), vec![2]);
+        assert_eq!(Staking::unbonding(&20), vec![(2, 30_000_000)]);
+        // the nomination records are not migrated again
+        assert_eq!(
+            Staking::nomination_record_of(&20, &10).nomination,
+            70_000_000
+        );
+    });
+}
@@ -131,6 +131,25 @@ impl<T: Trait> Module<T> {
         who.set_amount(value, to_add);
     }
 
+    /// Share of the target's jackpot the source would get by claiming now.
+    pub fn generic_dividend<U, V>(source: &U, target: &V) -> T::Balance
+    where
+        U: VoteWeight<T::BlockNumber>,
+        V: VoteWeight<T::BlockNumber> + Jackpot<T::Balance>,
+    {
+        let current_block = <system::Module<T>>::block_number();
+
+        let source_vote_weight = source.latest_acum_weight(current_block);
+        let target_vote_weight = target.latest_acum_weight(current_block);
+        if source_vote_weight == 0 || target_vote_weight == 0 {
+            return T::Balance::sa(0);
+        }
+
+        T::Balance::sa(
+            (source_vote_weight * target.jackpot().as_() as u128 / target_vote_weight) as u64,
+        )
+    }
+
     pub fn generic_claim<U, V>(source: &mut U, target: &mut V, who: &T::AccountId) -> Result
     where
         U: VoteWeight<T::BlockNumber>,
@@ -148,9 +167,7 @@ impl<T: Trait> Module<T> {
 
         let jackpot = target.jackpot();
 
-        let dividend = T::Balance::sa(
-            (source_vote_weight * jackpot.as_() as u128 / target_vote_weight) as u64,
-        );
+        let dividend = Self::generic_dividend(source, target);
 
         <balances::Module<T>>::reward(who, dividend)?;
 
@@ -32,6 +32,10 @@ extern crate sr_primitives as runtime_primitives;
 #[macro_use]
 extern crate srml_support as runtime_support;
 extern crate srml_balances as balances;
+#[cfg(test)]
+extern crate srml_consensus as consensus;
+#[cfg(test)]
+extern crate srml_session as session;
 extern crate srml_system as system;
 extern crate srml_timestamp as timestamp;
 
@@ -47,11 +51,14 @@ extern crate arml_tokenbalances as tokenbalances;
 
 extern crate arml_bridge_btc as btc;
 
+#[cfg(test)]
+mod mock;
 #[cfg(test)]
 mod tests;
 
 //use codec::{Codec, Decode, Encode};
 //use rstd::marker::PhantomData;
+use codec::{Decode, Input};
 use rstd::prelude::*;
 //use rstd::result::Result as StdResult;
 use runtime_primitives::traits::{As, CheckedAdd, CheckedSub, OnFinalise, Zero};
@@ -60,6 +67,11 @@ use runtime_support::{StorageMap, StorageValue};
 
 use system::ensure_signed;
 
+use arml_support::migration::{known_accounts, Migrate};
+use arml_support::storage::linked_node::{LinkedNodeCollection, MultiNodeIndex, Node, NodeT};
+use arml_support::CallFee;
+use arml_system::OnBlockStart;
+
 use financialrecords::{OnDepositToken, OnWithdrawToken};
 use pendingorders::OrderPair;
 use staking::{Jackpot, OnNewSessionForTokenStaking, OnReward, Validator, VoteWeight};
@@ -86,14 +98,183 @@ pub struct VirtualIntentionProfs<Balance: Default, BlockNumber: Default> {
     pub last_total_weight_update: BlockNumber,
 }
 
+/// Price and stake of a token its session reward was shared out by.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub struct SessionStake<Balance: Default, BlockNumber: Default> {
+    /// Last average price against the native token, zero if the pair had none.
+    pub price: u64,
+    /// Stake of all issued tokens in the native token, after the discount ratio.
+    pub discounted_stake: Balance,
+    pub updated_at: BlockNumber,
+}
+
+/// Most deposits an account keeps warming up separately for one token, a deposit beyond that
+/// is merged into the latest one.
+pub const MAX_WARMUP_DEPOSITS: usize = 16;
+
+/// Tokens of one deposit still inside the warm-up period.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub struct Warmup<BlockNumber> {
+    pub amount: u128,
+    /// Part of the hodling weight accrued by `amount` so far.
+    pub weight: u128,
+    /// Block at which `amount` starts to count as claimable weight.
+    pub mature_at: BlockNumber,
+}
+
+#[derive(PartialEq, Eq, Clone, Encode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
 pub struct HodlingRecord<BlockNumber>
 where
     BlockNumber: Default + As<u64> + Copy + Clone,
 {
     pub last_weight: u128,
     pub last_weight_update: BlockNumber,
+    /// Deposits still inside the warm-up period, oldest first.
+    pub warmups: Vec<Warmup<BlockNumber>>,
+}
+
+impl<BlockNumber> Decode for HodlingRecord<BlockNumber>
+where
+    BlockNumber: Decode + Default + As<u64> + Copy + Clone,
+{
+    fn decode<I: Input>(input: &mut I) -> Option<Self> {
+        let last_weight = u128::decode(input)?;
+        let last_weight_update = BlockNumber::decode(input)?;
+        // records written before the warm-up period end here
+        let warmups = Vec::<Warmup<BlockNumber>>::decode(input).unwrap_or_default();
+        Some(HodlingRecord {
+            last_weight,
+            last_weight_update,
+            warmups,
+        })
+    }
+}
+
+impl<BlockNumber> HodlingRecord<BlockNumber>
+where
+    BlockNumber: Default + As<u64> + Copy + Clone,
+{
+    /// Accrue the weight of the immature deposits up to `current_block`.
+    ///
+    /// Must be called before `last_weight_update` moves forward. Once a deposit matures, the
+    /// weight it accrued during the warm-up becomes claimable.
+    pub fn settle_warmup(&mut self, current_block: BlockNumber) {
+        let current = current_block.as_();
+        let last_update = self.last_weight_update.as_();
+        for warmup in self.warmups.iter_mut() {
+            let until = current.min(warmup.mature_at.as_());
+            if until > last_update {
+                warmup.weight += warmup.amount * (until - last_update) as u128;
+            }
+        }
+        self.warmups.retain(|w| current < w.mature_at.as_());
+    }
+
+    /// Put `value` freshly deposited tokens into warm-up until `mature_at`.
+    ///
+    /// Deposits already warming up keep their own maturity, unless there are
+    /// `MAX_WARMUP_DEPOSITS` of them and `value` is merged into the latest one.
+    pub fn start_warmup(&mut self, value: u128, mature_at: BlockNumber) {
+        if value == 0 {
+            return;
+        }
+        let merge = match self.warmups.last() {
+            Some(last) => {
+                last.mature_at.as_() == mature_at.as_() || self.warmups.len() >= MAX_WARMUP_DEPOSITS
+            }
+            None => false,
+        };
+        match self.warmups.last_mut() {
+            Some(last) if merge => {
+                last.amount += value;
+                last.mature_at = mature_at;
+            }
+            _ => self.warmups.push(Warmup {
+                amount: value,
+                weight: 0,
+                mature_at,
+            }),
+        }
+    }
+
+    /// Take `value` out of a `balance` of which the immature amount is a part, returns the
+    /// immature weight which is forfeited.
+    ///
+    /// The matured tokens are taken first, then the latest deposits. Only the immature part of
+    /// `value` forfeits its share of the weight accrued during the warm-up.
+    pub fn forfeit_warmup(&mut self, value: u128, balance: u128) -> u128 {
+        let matured = balance.saturating_sub(self.immature_amount());
+        let mut taken = value.saturating_sub(matured);
+        let mut forfeited = 0;
+        for warmup in self.warmups.iter_mut().rev() {
+            if taken == 0 {
+                break;
+            }
+            let part = taken.min(warmup.amount);
+            let lost = warmup.weight * part / warmup.amount;
+            warmup.amount -= part;
+            warmup.weight -= lost;
+            forfeited += lost;
+            taken -= part;
+        }
+        self.warmups.retain(|w| w.amount > 0);
+        self.last_weight = self.last_weight.saturating_sub(forfeited);
+        forfeited
+    }
+
+    /// Amount of all the deposits still warming up.
+    pub fn immature_amount(&self) -> u128 {
+        self.warmups.iter().map(|w| w.amount).sum()
+    }
+
+    /// Part of `last_weight` accrued by deposits still warming up.
+    pub fn immature_weight(&self) -> u128 {
+        self.warmups.iter().map(|w| w.weight).sum()
+    }
+
+    /// Block at which the next deposit matures, if any is warming up.
+    pub fn next_maturity(&self) -> Option<BlockNumber> {
+        self.warmups.first().map(|w| w.mature_at)
+    }
+
+    /// Weight which can be claimed at the last update.
+    pub fn mature_weight(&self) -> u128 {
+        self.last_weight.saturating_sub(self.immature_weight())
+    }
+}
+
+/// Account holding a token, node of the linked list of the token holders.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, Debug))]
+pub struct Holder<AccountId: Default> {
+    pub account: AccountId,
+    pub sym: Symbol,
+}
+
+impl<AccountId> NodeT for Holder<AccountId>
+where
+    AccountId: codec::Codec + Clone + Eq + Default,
+{
+    type Index = (AccountId, Symbol);
+
+    fn index(&self) -> (AccountId, Symbol) {
+        (self.account.clone(), self.sym.clone())
+    }
+}
+
+type HolderNode<T> = Node<Holder<<T as system::Trait>::AccountId>>;
+
+/// Holders of every token, one linked list per symbol.
+struct LinkedHolders<T: Trait>(rstd::marker::PhantomData<T>);
+
+impl<T: Trait> LinkedNodeCollection for LinkedHolders<T> {
+    type Header = HoldersHeader<T>;
+    type NodeMap = HolderNodeFor<T>;
+    type Tail = HoldersTail<T>;
+    type Length = HoldersCount<T>;
 }
 
 pub struct IntentionProfsWrapper<'a, T: Trait> {
@@ -176,6 +357,19 @@ decl_event!(
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn claim(origin, sym: Symbol) -> Result;
+        fn claim_all(origin) -> Result;
+
+        fn set_warmup_period(sym: Symbol, period: T::BlockNumber) -> Result;
+    }
+}
+
+impl<T: Trait> CallFee<T::Balance> for Call<T> {
+    fn call_fee(&self) -> T::Balance {
+        match *self {
+            // `claim_all` pays the fee once for every token
+            Call::claim(..) | Call::claim_all(..) => Module::<T>::fee(),
+            _ => Zero::zero(),
+        }
     }
 }
 
@@ -185,15 +379,66 @@ impl<T: Trait> OnFinalise<T::BlockNumber> for Module<T> {
     }
 }
 
+impl<T: Trait> OnBlockStart for Module<T> {
+    fn on_block_start() {
+        Self::on_runtime_upgrade();
+    }
+}
+
+impl<T: Trait> Migrate for Module<T> {
+    const VERSION_KEY: &'static [u8] = b"TokenStaking StorageVersion";
+    const STORAGE_VERSION: u32 = 1;
+
+    fn migrate_step(from: u32) {
+        // v1: hodling records carry the warm-up fields and every holder is indexed, look the
+        // records up for all accounts, rewrite them in the new layout and add their holders
+        if from == 0 {
+            let syms = tokenbalances::Module::<T>::valid_token_list();
+            for who in known_accounts::<T>() {
+                for sym in syms.iter() {
+                    let key = (who.clone(), sym.clone());
+                    if HodlingRecordFor::<T>::exists(&key) == false {
+                        continue;
+                    }
+                    HodlingRecordFor::<T>::insert(&key, HodlingRecordFor::<T>::get(&key));
+                    if Self::holder_node_for(&key).is_none() {
+                        Self::add_holder(&who, sym);
+                    }
+                }
+            }
+        }
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as TokenStkaing {
         pub VirtualProfsFor get(virtual_profs_for): map Symbol => VirtualIntentionProfs<T::Balance, T::BlockNumber>;
         pub HodlingRecordFor get(hodling_record_for): map (T::AccountId, Symbol) => HodlingRecord<T::BlockNumber>;
+        /// First and last accounts which have a hodling record of the token.
+        pub HoldersHeader get(holders_header): map Symbol => Option<MultiNodeIndex<Symbol, Holder<T::AccountId>>>;
+        pub HoldersTail get(holders_tail): map Symbol => Option<MultiNodeIndex<Symbol, Holder<T::AccountId>>>;
+        pub HolderNodeFor get(holder_node_for): map (T::AccountId, Symbol) => Option<Node<Holder<T::AccountId>>>;
+        /// Number of accounts which have a hodling record of the token.
+        pub HoldersCount get(holders_count): map Symbol => u32;
+        /// Tokens the account has a hodling record of.
+        pub HeldSymbolsOf get(held_symbols_of): map T::AccountId => Vec<Symbol>;
 
         pub DiscountRatioFor get(discount_ratio_for): map Symbol => (u32, u32) = (1, 2);
+        /// Blocks a deposit or received tokens have to be held before their vote weight can be
+        /// claimed. A non-zero period turns off the deposit reward of the token.
+        pub WarmupPeriodFor get(warmup_period_for): map Symbol => T::BlockNumber;
+        /// Price and discounted stake of the token at the last new session.
+        pub LastSessionStakeFor get(last_session_stake_for): map Symbol => SessionStake<T::Balance, T::BlockNumber>;
 
         pub Fee get(fee) config(): T::Balance;
     }
+
+    add_extra_genesis {
+        build(|storage: &mut runtime_primitives::StorageMap, _: &GenesisConfig<T>| {
+            let (version_key, version) = arml_support::migration::genesis_version::<Module<T>>();
+            storage.insert(version_key, version);
+        });
+    }
 }
 
 impl<T: Trait> Module<T> {
@@ -207,11 +452,51 @@ impl<T: Trait> Module<T> {
 impl<T: Trait> Module<T> {
     fn claim(origin: T::Origin, sym: Symbol) -> Result {
         let who = ensure_signed(origin)?;
-        arml_support::Module::<T>::handle_fee_before(&who, Self::fee(), true, || Ok(()))?;
-
-        let mut profs = Module::<T>::virtual_profs_for(&sym);
         let key = (who.clone(), sym.clone());
-        let mut record = Module::<T>::hodling_record_for(&key);
+        if HodlingRecordFor::<T>::exists(&key) == false {
+            return Err("no hodling record of this token.");
+        }
+        // the fee is only paid for a settled claim
+        arml_support::Module::<T>::handle_fee_after(&who, Self::fee(), true, || {
+            Self::settle_claim(&who, &sym)
+        })
+    }
+
+    /// Claim the dividend of every held token with a non-zero vote weight, paying the fee once.
+    fn claim_all(origin: T::Origin) -> Result {
+        let who = ensure_signed(origin)?;
+        let syms: Vec<Symbol> = Self::held_symbols_of(&who)
+            .into_iter()
+            .filter(|sym| Self::hodling_weight_of(&who, sym) > 0)
+            .collect();
+        if syms.is_empty() {
+            return Err("no token vote weight to claim.");
+        }
+        arml_support::Module::<T>::handle_fee_after(&who, Self::fee(), true, || {
+            // a token still warming up must not stop the others from being claimed
+            let results: Vec<Result> = syms
+                .iter()
+                .map(|sym| Self::settle_claim(&who, sym))
+                .collect();
+            if results.iter().any(|r| r.is_ok()) {
+                return Ok(());
+            }
+            // nothing settled, nothing to pay for
+            results[0]
+        })
+    }
+
+    /// Pay `who` the dividend of `sym` out of its virtual intention jackpot.
+    fn settle_claim(who: &T::AccountId, sym: &Symbol) -> Result {
+        let (mut profs, mut record) = Self::updated_profs_and_record(who, sym);
+
+        // weight accrued during the warm-up is not claimable yet
+        let immature = record.immature_weight();
+        if record.mature_weight() == 0 && immature > 0 {
+            return Err("token vote weight is still warming up.");
+        }
+        record.last_weight -= immature;
+        profs.last_total_weight -= immature;
 
         {
             let mut iprofs = IntentionProfsWrapper::<T> {
@@ -223,116 +508,300 @@ impl<T: Trait> Module<T> {
                 account: who.clone(),
                 record: &mut record,
             };
-            staking::Module::<T>::generic_claim(&mut hodling, &mut iprofs, &who)?;
+            staking::Module::<T>::generic_claim(&mut hodling, &mut iprofs, who)?;
         }
 
-        VirtualProfsFor::<T>::insert(&sym, profs);
-        HodlingRecordFor::<T>::insert(key, record);
+        record.last_weight += immature;
+        profs.last_total_weight += immature;
 
-        Self::deposit_event(RawEvent::TokenRewardClaim(who, sym));
+        VirtualProfsFor::<T>::insert(sym, profs);
+        let balance = tokenbalances::Module::<T>::total_token_of(who, sym).as_();
+        Self::store_hodling_record(who, sym, record, balance);
+
+        Self::deposit_event(RawEvent::TokenRewardClaim(who.clone(), sym.clone()));
         Ok(())
     }
-}
-
-// trigger
-impl<T: Trait> OnMoveToken<T::AccountId, T::TokenBalance> for Module<T> {
-    fn on_move_token(from: &T::AccountId, to: &T::AccountId, sym: &Symbol, value: T::TokenBalance) {
-        if is_valid_exchange_token::<T>(sym) == false {
-            return;
-        }
-
-        if VirtualProfsFor::<T>::exists(sym) == false {
-            return;
-        }
-
-        let mut profs = Self::virtual_profs_for(sym);
-        let key_from = (from.clone(), sym.clone());
-        let key_to = (to.clone(), sym.clone());
-        if HodlingRecordFor::<T>::exists(&key_from) == false {
-            return;
-        }
-
-        let mut from_record = Self::hodling_record_for(&key_from);
-        let mut to_record = Self::hodling_record_for(&key_to);
 
+    /// Virtual intention of `sym` and hodling record of `who`, with both weights brought up to
+    /// the current block. Nothing is written back.
+    fn updated_profs_and_record(
+        who: &T::AccountId,
+        sym: &Symbol,
+    ) -> (
+        VirtualIntentionProfs<T::Balance, T::BlockNumber>,
+        HodlingRecord<T::BlockNumber>,
+    ) {
+        let mut profs = Module::<T>::virtual_profs_for(sym);
+        let mut record = Module::<T>::hodling_record_for(&(who.clone(), sym.clone()));
+
+        record.settle_warmup(<system::Module<T>>::block_number());
         {
             let mut iprofs = IntentionProfsWrapper::<T> {
                 sym: sym.clone(),
                 profs: &mut profs,
             };
-            let mut from_hodling = HodlingRecordWrapper::<T> {
-                sym: sym.clone(),
-                account: from.clone(),
-                record: &mut from_record,
-            };
-            let mut to_hodling = HodlingRecordWrapper::<T> {
+            let mut hodling = HodlingRecordWrapper::<T> {
                 sym: sym.clone(),
-                account: to.clone(),
-                record: &mut to_record,
+                account: who.clone(),
+                record: &mut record,
             };
+            // bring both weights up to date before splitting off the immature part
+            staking::Module::<T>::update_vote_weight_both_way(&mut iprofs, &mut hodling, 0, true);
+        }
+        (profs, record)
+    }
 
-            // sub from
-            staking::Module::<T>::update_vote_weight_both_way(
-                &mut iprofs,
-                &mut from_hodling,
-                value.as_(),
-                false,
-            );
-            // add to
-            staking::Module::<T>::update_vote_weight_both_way(
-                &mut iprofs,
-                &mut to_hodling,
-                value.as_(),
-                true,
-            );
+    /// Virtual intention of `sym`, a fresh one accruing from the current block if there is none yet.
+    fn virtual_profs_or_new(sym: &Symbol) -> VirtualIntentionProfs<T::Balance, T::BlockNumber> {
+        if VirtualProfsFor::<T>::exists(sym) {
+            return Self::virtual_profs_for(sym);
+        }
+        VirtualIntentionProfs {
+            last_total_weight_update: <system::Module<T>>::block_number(),
+            ..Default::default()
         }
+    }
+
+    /// Hodling record of `who` for `sym`, a fresh one accruing from the current block if there
+    /// is none yet. New records are added to the holders of `sym` and to `HeldSymbolsOf`.
+    fn hodling_record_or_new(who: &T::AccountId, sym: &Symbol) -> HodlingRecord<T::BlockNumber> {
+        let key = (who.clone(), sym.clone());
+        if HodlingRecordFor::<T>::exists(&key) {
+            return Self::hodling_record_for(&key);
+        }
+        Self::add_holder(who, sym);
+        HodlingRecord {
+            last_weight_update: <system::Module<T>>::block_number(),
+            ..Default::default()
+        }
+    }
+
+    fn add_holder(who: &T::AccountId, sym: &Symbol) {
+        let node = Node::new(Holder {
+            account: who.clone(),
+            sym: sym.clone(),
+        });
+        let tail = Self::holders_tail(sym).and_then(|tail| Self::holder_node_for(&tail.index()));
+        match tail {
+            Some(mut tail) => {
+                if let Err(e) = tail
+                    .add_option_node_after_withkey::<LinkedHolders<T>, Symbol>(node, sym.clone())
+                {
+                    runtime_io::print(e);
+                }
+            }
+            None => node.init_storage_withkey::<LinkedHolders<T>, Symbol>(sym.clone()),
+        }
+        <HeldSymbolsOf<T>>::mutate(who, |syms| syms.push(sym.clone()));
+    }
+
+    /// Drop the hodling record of `who` for `sym` together with its place among the holders.
+    ///
+    /// Only for a record left without tokens and vote weight, which adds nothing to the total.
+    fn remove_holder(who: &T::AccountId, sym: &Symbol) {
+        let key = (who.clone(), sym.clone());
+        HodlingRecordFor::<T>::remove(&key);
+        if let Some(mut node) = Self::holder_node_for(&key) {
+            if let Err(e) = node.remove_option_node_withkey::<LinkedHolders<T>, Symbol>(sym.clone())
+            {
+                runtime_io::print(e);
+            }
+        }
+        let mut syms = Self::held_symbols_of(who);
+        syms.retain(|s| s != sym);
+        if syms.is_empty() {
+            <HeldSymbolsOf<T>>::remove(who);
+        } else {
+            <HeldSymbolsOf<T>>::insert(who, syms);
+        }
+    }
+
+    /// Write back the hodling record of `who`, or drop it once `balance` and its weight are
+    /// both zero.
+    fn store_hodling_record(
+        who: &T::AccountId,
+        sym: &Symbol,
+        record: HodlingRecord<T::BlockNumber>,
+        balance: u128,
+    ) {
+        if balance == 0 && record.last_weight == 0 {
+            Self::remove_holder(who, sym);
+        } else {
+            HodlingRecordFor::<T>::insert((who.clone(), sym.clone()), record);
+        }
+    }
+
+    /// Accounts which have a hodling record of `sym`, in the order they got it.
+    pub fn holders_for(sym: &Symbol) -> Vec<T::AccountId> {
+        HolderNode::<T>::iter_withkey::<LinkedHolders<T>, Symbol>(sym)
+            .map(|node| node.data.account)
+            .collect()
+    }
+
+    /// Latest vote weight of `who` for `sym`.
+    pub fn hodling_weight_of(who: &T::AccountId, sym: &Symbol) -> u128 {
+        let mut record = Self::hodling_record_for(&(who.clone(), sym.clone()));
+        let hodling = HodlingRecordWrapper::<T> {
+            sym: sym.clone(),
+            account: who.clone(),
+            record: &mut record,
+        };
+        hodling.latest_acum_weight(<system::Module<T>>::block_number())
+    }
+
+    /// Sum of the latest vote weights of all holders of `sym`.
+    ///
+    /// Walks every holder of the token, for tests and RPC only, never call it from a dispatch.
+    pub fn total_hodling_weight(sym: &Symbol) -> u128 {
+        HolderNode::<T>::iter_withkey::<LinkedHolders<T>, Symbol>(sym)
+            .map(|node| Self::hodling_weight_of(&node.data.account, sym))
+            .sum()
+    }
+
+    /// Latest total vote weight of the virtual intention of `sym`.
+    pub fn total_virtual_weight(sym: &Symbol) -> u128 {
+        let mut profs = Self::virtual_profs_for(sym);
+        let iprofs = IntentionProfsWrapper::<T> {
+            sym: sym.clone(),
+            profs: &mut profs,
+        };
+        iprofs.latest_acum_weight(<system::Module<T>>::block_number())
+    }
+
+    /// Whether the hodling weights of `sym` add up to the weight of its virtual intention.
+    pub fn weight_invariant_holds(sym: &Symbol) -> bool {
+        Self::total_hodling_weight(sym) == Self::total_virtual_weight(sym)
+    }
+
+    /// Tokens taking part in token staking, all valid exchange tokens except the native one.
+    pub fn staking_symbols() -> Vec<Symbol> {
+        tokenbalances::Module::<T>::valid_token_list()
+            .into_iter()
+            .filter(|sym| sym.as_slice() != T::AKRO_SYMBOL && is_valid_exchange_token::<T>(sym))
+            .collect()
+    }
+
+    /// Staking tokens with their virtual intention and its latest total weight.
+    pub fn staking_tokens() -> Vec<(
+        Symbol,
+        VirtualIntentionProfs<T::Balance, T::BlockNumber>,
+        u128,
+    )> {
+        Self::staking_symbols()
+            .into_iter()
+            .map(|sym| {
+                let weight = Self::total_virtual_weight(&sym);
+                (sym.clone(), Self::virtual_profs_for(&sym), weight)
+            })
+            .collect()
+    }
+
+    /// Dividend `who` would get by claiming `sym` now, zero while the weight is warming up.
+    pub fn claimable_dividend(who: &T::AccountId, sym: &Symbol) -> T::Balance {
+        let (mut profs, mut record) = Self::updated_profs_and_record(who, sym);
+        let immature = record.immature_weight();
+        record.last_weight -= immature;
+        profs.last_total_weight -= immature;
+
+        let iprofs = IntentionProfsWrapper::<T> {
+            sym: sym.clone(),
+            profs: &mut profs,
+        };
+        let hodling = HodlingRecordWrapper::<T> {
+            sym: sym.clone(),
+            account: who.clone(),
+            record: &mut record,
+        };
+        staking::Module::<T>::generic_dividend(&hodling, &iprofs)
+    }
+
+    /// Hodling records of `who` with the latest weight and the claimable dividend of each token.
+    pub fn hodling_records_with_dividend(
+        who: &T::AccountId,
+    ) -> Vec<(Symbol, HodlingRecord<T::BlockNumber>, u128, T::Balance)> {
+        Self::held_symbols_of(who)
+            .into_iter()
+            .map(|sym| {
+                let record = Self::hodling_record_for(&(who.clone(), sym.clone()));
+                let weight = Self::hodling_weight_of(who, &sym);
+                let dividend = Self::claimable_dividend(who, &sym);
+                (sym, record, weight, dividend)
+            })
+            .collect()
+    }
+
+    /// Price and discounted stake of every staking token at the last new session.
+    pub fn last_session_stakes() -> Vec<(Symbol, SessionStake<T::Balance, T::BlockNumber>)> {
+        Self::staking_symbols()
+            .into_iter()
+            .map(|sym| {
+                let stake = Self::last_session_stake_for(&sym);
+                (sym, stake)
+            })
+            .collect()
+    }
+
+    /// Set the warm-up period of a token.
+    fn set_warmup_period(sym: Symbol, period: T::BlockNumber) -> Result {
+        <WarmupPeriodFor<T>>::insert(sym, period);
+        Ok(())
+    }
+}
+
+// trigger
+impl<T: Trait> OnMoveToken<T::AccountId, T::TokenBalance> for Module<T> {
+    fn on_move_token(from: &T::AccountId, to: &T::AccountId, sym: &Symbol, value: T::TokenBalance) {
+        if is_valid_exchange_token::<T>(sym) == false {
+            return;
+        }
+
+        let mut profs = Module::<T>::virtual_profs_or_new(sym);
+        apply_hodling_change::<T>(&mut profs, from, sym, value.as_(), HodlingChange::Remove);
+        apply_hodling_change::<T>(&mut profs, to, sym, value.as_(), HodlingChange::Receive);
         VirtualProfsFor::<T>::insert(sym, profs);
-        HodlingRecordFor::<T>::insert(key_from, from_record);
-        HodlingRecordFor::<T>::insert(key_to, to_record);
     }
 }
 
 impl<T: Trait> OnNewSessionForTokenStaking<T::AccountId, T::Balance> for Module<T> {
-    fn token_staking_info() -> Vec<(Validator<T::AccountId>, T::Balance)> {
+    fn on_new_session() {
         runtime_io::print("new session token stake  --sym--pcx--average_price");
-        let mut syms: Vec<Symbol> = tokenbalances::Module::<T>::valid_token_list();
-        if let Some(index) = syms.iter().position(|x| x.as_slice() == T::AKRO_SYMBOL) {
-            syms.remove(index);
+        for sym in Module::<T>::staking_symbols() {
+            let o = OrderPair {
+                first: T::AKRO_SYMBOL.to_vec(),
+                second: sym.clone(),
+            };
+            // get price
+            let price = pendingorders::Module::<T>::last_average_price(&o)
+                .map_or(0, |price| price.as_() as u64);
+            let pcx_amount = if price != 0 {
+                // get token amount
+                let token_amount: T::TokenBalance = tokenbalances::Module::<T>::total_token(&sym);
+                let r: T::TokenBalance = token_amount / As::sa(price);
+                let pcx: T::Balance = As::sa(r.as_() as u64);
+                token_pcx_discount::<T>(&sym, pcx)
+            } else {
+                Zero::zero()
+            };
+            // log
+            runtime_io::print(sym.as_slice());
+            runtime_io::print(pcx_amount.as_() as u64);
+            runtime_io::print(price);
+
+            <LastSessionStakeFor<T>>::insert(
+                sym,
+                SessionStake {
+                    price,
+                    discounted_stake: pcx_amount,
+                    updated_at: <system::Module<T>>::block_number(),
+                },
+            );
         }
+    }
 
-        syms.into_iter()
-            .filter(|s| is_valid_exchange_token::<T>(s))
-            .map(|sym| {
-                let o = OrderPair {
-                    first: T::AKRO_SYMBOL.to_vec(),
-                    second: sym.clone(),
-                };
-                // get price
-                let pcx_amount =
-                    if let Some(price) = pendingorders::Module::<T>::last_average_price(&o) {
-                        // get token amount
-                        let token_amount: T::TokenBalance =
-                            tokenbalances::Module::<T>::total_token(&sym);
-                        if price != Zero::zero() {
-                            let r: T::TokenBalance = token_amount / As::sa(price.as_());
-                            let pcx: T::Balance = As::sa(r.as_() as u64);
-                            token_pcx_discount::<T>(&sym, pcx)
-                        } else {
-                            Zero::zero()
-                        }
-                    } else {
-                        Zero::zero()
-                    };
-                // log
-                runtime_io::print(sym.as_slice());
-                runtime_io::print(pcx_amount.as_() as u64);
-                match pendingorders::Module::<T>::last_average_price(o) {
-                    Some(n) => runtime_io::print(n.as_() as u64),
-                    None => runtime_io::print("None"),
-                }
-
-                (Validator::Token(sym), pcx_amount)
-            })
+    fn token_staking_info() -> Vec<(Validator<T::AccountId>, T::Balance)> {
+        Module::<T>::last_session_stakes()
+            .into_iter()
+            .map(|(sym, stake)| (Validator::Token(sym), stake.discounted_stake))
             .collect()
     }
 }
@@ -351,10 +820,10 @@ impl<T: Trait> OnReward<T::AccountId, T::Balance> for Module<T> {
                 runtime_io::print("reward for token, ---sym---newjackpot");
                 runtime_io::print(sym.as_slice());
 
-                VirtualProfsFor::<T>::mutate(sym, |profs| {
-                    profs.jackpot += b;
-                    runtime_io::print(profs.jackpot.as_());
-                });
+                let mut profs = Module::<T>::virtual_profs_or_new(sym);
+                profs.jackpot += b;
+                runtime_io::print(profs.jackpot.as_());
+                VirtualProfsFor::<T>::insert(sym, profs);
             }
             _ => { /*do nothing*/ }
         }
@@ -372,6 +841,11 @@ impl<T: Trait> OnDepositToken<T::AccountId, T::TokenBalance> for Module<T> {
     }
 }
 
+/// Pay the depositor of a bridged token its share of the jackpot for the blocks the deposit
+/// took to become irreversible.
+///
+/// Nothing is paid for a token with a warm-up period: the reward would be claimable at once,
+/// while the warm-up defers the weight of the same deposit until it matures.
 fn deposit_reward<T: Trait>(who: &T::AccountId, sym: &Symbol, value: T::TokenBalance) {
     runtime_io::print("deposit reward  --sym--block_count--reward--new_jackpot--new_balance");
     runtime_io::print(sym.as_slice());
@@ -388,6 +862,11 @@ fn deposit_reward<T: Trait>(who: &T::AccountId, sym: &Symbol, value: T::TokenBal
     };
     runtime_io::print(block_count as u64);
 
+    // with a warm-up period the deposit earns through the regular claim once it matures
+    if !Module::<T>::warmup_period_for(sym).is_zero() {
+        return;
+    }
+
     let mut profs = Module::<T>::virtual_profs_for(sym);
     if profs.last_total_weight == 0 {
         return;
@@ -428,41 +907,92 @@ impl<T: Trait> OnWithdrawToken<T::AccountId, T::TokenBalance> for Module<T> {
         if is_valid_exchange_token::<T>(sym) == false {
             return;
         }
-        if VirtualProfsFor::<T>::exists(sym) == false {
-            return;
-        }
-        if HodlingRecordFor::<T>::exists((who.clone(), sym.clone())) == false {
-            return;
-        }
         change_vote::<T>(who, sym, value, false)
     }
 }
 
 fn change_vote<T: Trait>(who: &T::AccountId, sym: &Symbol, value: T::TokenBalance, is_add: bool) {
-    let mut profs = Module::<T>::virtual_profs_for(sym);
-    let key = (who.clone(), sym.clone());
-    let mut record = Module::<T>::hodling_record_for(&key);
+    let change = if is_add {
+        HodlingChange::Deposit
+    } else {
+        HodlingChange::Remove
+    };
 
+    let mut profs = Module::<T>::virtual_profs_or_new(sym);
+    apply_hodling_change::<T>(&mut profs, who, sym, value.as_(), change);
+    VirtualProfsFor::<T>::insert(sym, profs);
+}
+
+/// How a token balance change affects a hodling record.
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum HodlingChange {
+    /// Tokens deposited from outside the chain, subject to the warm-up period.
+    Deposit,
+    /// Tokens received from another account, subject to the warm-up period as well.
+    Receive,
+    /// Tokens withdrawn or sent to another account.
+    Remove,
+}
+
+/// Settle the vote weight of `who` and of the virtual intention up to the current block,
+/// then apply `change`.
+///
+/// The triggers run before the token balance changes, so the weight accrued so far is
+/// computed with the old amounts. Deposit, withdraw and move all go through here, which
+/// keeps the sum of the hodling weights equal to the total weight of the virtual intention.
+fn apply_hodling_change<T: Trait>(
+    profs: &mut VirtualIntentionProfs<T::Balance, T::BlockNumber>,
+    who: &T::AccountId,
+    sym: &Symbol,
+    value: u128,
+    change: HodlingChange,
+) {
+    let mut record = Module::<T>::hodling_record_or_new(who, sym);
+
+    let current_block = <system::Module<T>>::block_number();
+    // the trigger runs before the balance changes
+    let balance: u128 = tokenbalances::Module::<T>::total_token_of(who, sym).as_();
+    record.settle_warmup(current_block);
     {
         let mut iprofs = IntentionProfsWrapper::<T> {
             sym: sym.clone(),
-            profs: &mut profs,
+            profs,
         };
         let mut hodling = HodlingRecordWrapper::<T> {
             sym: sym.clone(),
             account: who.clone(),
             record: &mut record,
         };
-        // sub from
         staking::Module::<T>::update_vote_weight_both_way(
             &mut iprofs,
             &mut hodling,
-            value.as_(),
-            is_add,
+            value,
+            change != HodlingChange::Remove,
         );
     }
-    VirtualProfsFor::<T>::insert(sym, profs);
-    HodlingRecordFor::<T>::insert(key, record);
+
+    match change {
+        // received tokens warm up again, otherwise a transfer right after the deposit would
+        // skip the warm-up period
+        HodlingChange::Deposit | HodlingChange::Receive => {
+            let warmup = Module::<T>::warmup_period_for(sym);
+            if !warmup.is_zero() {
+                record.start_warmup(value, current_block + warmup);
+            }
+        }
+        HodlingChange::Remove => {
+            // tokens moved out before maturing lose the weight they accrued so far
+            let forfeited = record.forfeit_warmup(value, balance);
+            profs.last_total_weight -= forfeited;
+        }
+    }
+
+    let balance = if change == HodlingChange::Remove {
+        balance.saturating_sub(value)
+    } else {
+        balance + value
+    };
+    Module::<T>::store_hodling_record(who, sym, record, balance);
 }
 
 fn is_valid_exchange_token<T: Trait>(sym: &Symbol) -> bool {
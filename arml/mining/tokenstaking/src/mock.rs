@@ -0,0 +1,220 @@
+// Copyright 2018 Akropolis.
+
+//! Test utilities
+
+#![cfg(test)]
+
+use financialrecords::{OnDepositToken, OnWithdrawToken};
+use pendingorders::OrderPair;
+use runtime_io;
+use runtime_primitives::testing::{Digest, DigestItem, Header};
+use runtime_primitives::traits::Identity;
+use runtime_primitives::BuildStorage;
+use staking::{OnReward, Validator};
+use substrate_primitives::{Blake2Hasher, H256};
+use tokenbalances::{DescString, Symbol, SymbolString, Token};
+use {
+    arml_support, arml_system, associations, balances, btc, consensus, financialrecords,
+    pendingorders, session, staking, system, timestamp, tokenbalances, GenesisConfig, Module,
+    Trait,
+};
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+// Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Test;
+
+impl consensus::Trait for Test {
+    const NOTE_OFFLINE_POSITION: u32 = 1;
+    type Log = DigestItem;
+    type SessionKey = u64;
+    type OnOfflineValidator = ();
+}
+impl system::Trait for Test {
+    type Origin = Origin;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = ::runtime_primitives::traits::BlakeTwo256;
+    type Digest = Digest;
+    type AccountId = u64;
+    type Header = Header;
+    type Event = ();
+    type Log = DigestItem;
+}
+impl balances::Trait for Test {
+    type Balance = u64;
+    type AccountIndex = u64;
+    type OnFreeBalanceZero = Staking;
+    type EnsureAccountLiquid = Staking;
+    type Event = ();
+}
+impl session::Trait for Test {
+    type ConvertAccountIdToSessionKey = Identity;
+    type OnSessionChange = Staking;
+    type Event = ();
+}
+impl timestamp::Trait for Test {
+    const TIMESTAMP_SET_POSITION: u32 = 0;
+    type Moment = u64;
+}
+impl arml_support::Trait for Test {
+    type Event = ();
+}
+impl arml_system::Trait for Test {
+    type OnBlockStart = (Staking, TokenStaking);
+}
+impl associations::Trait for Test {
+    type OnCalcFee = arml_support::Module<Test>;
+    type Event = ();
+}
+pub type TokenBalance = u128;
+impl tokenbalances::Trait for Test {
+    const AKRO_SYMBOL: SymbolString = b"pcx";
+    const AKRO_TOKEN_DESC: DescString = b"this is pcx for mock";
+    type TokenBalance = TokenBalance;
+    type OnMoveToken = TokenStaking;
+    type Event = ();
+}
+impl staking::Trait for Test {
+    type OnNewSessionForTokenStaking = TokenStaking;
+    type OnRewardMinted = ();
+    type OnReward = TokenStaking;
+    type Event = ();
+}
+impl pendingorders::Trait for Test {
+    type Amount = u128;
+    type Price = u128;
+    type Event = ();
+}
+impl financialrecords::Trait for Test {
+    type Event = ();
+    type OnDepositToken = TokenStaking;
+    type OnWithdrawToken = TokenStaking;
+}
+impl btc::Trait for Test {
+    type Event = ();
+}
+impl Trait for Test {
+    type Event = ();
+}
+
+/// Token held in the tests, it can be exchanged against pcx.
+pub fn sym() -> Symbol {
+    b"x-btc".to_vec()
+}
+
+pub fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+    let mut t = system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+    t.extend(
+        consensus::GenesisConfig::<Test> {
+            code: vec![],
+            authorities: vec![],
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        session::GenesisConfig::<Test> {
+            session_length: 1,
+            validators: vec![10],
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        balances::GenesisConfig::<Test> {
+            balances: vec![(3, 100), (10, 1000)],
+            transaction_base_fee: 0,
+            transaction_byte_fee: 0,
+            existential_deposit: 0,
+            transfer_fee: 0,
+            creation_fee: 0,
+            reclaim_rebate: 0,
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        arml_system::GenesisConfig::<Test> {
+            death_account: 100,
+            fee_buy_account: 101,
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        staking::GenesisConfig::<Test> {
+            sessions_per_era: 1,
+            intentions: vec![10],
+            validator_count: 1,
+            minimum_validator_count: 1,
+            ..Default::default()
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        tokenbalances::GenesisConfig::<Test> {
+            token_list: vec![(Token::new(sym(), b"x-btc for mock".to_vec(), 8), vec![])],
+            transfer_token_fee: 0,
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        pendingorders::GenesisConfig::<Test> {
+            pair_list: vec![(
+                OrderPair {
+                    first: b"pcx".to_vec(),
+                    second: sym(),
+                },
+                8,
+            )],
+            ..Default::default()
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    t.extend(
+        timestamp::GenesisConfig::<Test> { period: 5 }
+            .build_storage()
+            .unwrap(),
+    );
+    t.extend(GenesisConfig::<Test> { fee: 0 }.build_storage().unwrap());
+    runtime_io::TestExternalities::new(t)
+}
+
+/// Deposit `value` of the test token to `who` the way the bridge does, trigger first.
+pub fn deposit(who: u64, value: TokenBalance) {
+    TokenStaking::on_deposit_token(&who, &sym(), value);
+    TokenBalances::issue(&who, &sym(), value).unwrap();
+}
+
+/// Withdraw `value` of the test token from `who`, trigger first.
+pub fn withdraw(who: u64, value: TokenBalance) {
+    TokenStaking::on_withdraw_token(&who, &sym(), value);
+    TokenBalances::destroy(&who, &sym(), value).unwrap();
+}
+
+/// Move `value` of the test token, tokenbalances calls the `OnMoveToken` trigger.
+pub fn transfer(from: u64, to: u64, value: TokenBalance) {
+    TokenBalances::move_free_token(&from, &to, &sym(), value).unwrap();
+}
+
+/// Add `value` to the jackpot of the test token as a session reward would.
+pub fn reward(value: u64) {
+    TokenStaking::on_reward(&Validator::Token(sym()), value);
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = balances::Module<Test>;
+pub type Session = session::Module<Test>;
+pub type Staking = staking::Module<Test>;
+pub type TokenBalances = tokenbalances::Module<Test>;
+pub type TokenStaking = Module<Test>;
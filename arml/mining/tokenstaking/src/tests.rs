@@ -1,4 +1,411 @@
+use super::*;
+use codec::Encode;
+use mock::{
+    deposit, new_test_ext, reward, sym, transfer, withdraw, Balances, Origin, Session, System,
+    Test, TokenBalances, TokenStaking,
+};
+use runtime_io::with_externalities;
+
 #[test]
 fn it_works() {
     assert_eq!(2 + 2, 4);
 }
+
+#[test]
+fn warmup_weight_matures() {
+    let mut record: HodlingRecord<u64> = HodlingRecord::default();
+    // deposit 10 at block 100, warm-up of 50 blocks
+    record.last_weight_update = 100;
+    record.start_warmup(10, 150);
+
+    // block 120: 200 weight accrued, none of it claimable
+    record.settle_warmup(120);
+    record.last_weight = 10 * 20;
+    record.last_weight_update = 120;
+    assert_eq!(record.immature_weight(), 200);
+    assert_eq!(record.mature_weight(), 0);
+
+    // block 170: matured, everything accrued is claimable
+    record.settle_warmup(170);
+    record.last_weight += 10 * 50;
+    record.last_weight_update = 170;
+    assert_eq!(record.immature_amount(), 0);
+    assert_eq!(record.immature_weight(), 0);
+    assert_eq!(record.mature_weight(), 700);
+}
+
+#[test]
+fn warmup_only_counts_new_deposit() {
+    let mut record: HodlingRecord<u64> = HodlingRecord::default();
+    // 100 matured tokens, then 10 more deposited at block 100
+    record.last_weight = 1_000;
+    record.last_weight_update = 100;
+    record.start_warmup(10, 150);
+
+    record.settle_warmup(110);
+    record.last_weight += 110 * 10;
+    record.last_weight_update = 110;
+    assert_eq!(record.immature_weight(), 100);
+    assert_eq!(record.mature_weight(), 2_000);
+}
+
+#[test]
+fn withdraw_during_warmup_forfeits() {
+    let mut record: HodlingRecord<u64> = HodlingRecord::default();
+    record.last_weight_update = 100;
+    record.start_warmup(10, 150);
+
+    record.settle_warmup(110);
+    record.last_weight = 100;
+    record.last_weight_update = 110;
+
+    assert_eq!(record.forfeit_warmup(10, 10), 100);
+    assert_eq!(record.last_weight, 0);
+    assert_eq!(record.immature_amount(), 0);
+
+    // nothing left to forfeit
+    assert_eq!(record.forfeit_warmup(10, 10), 0);
+}
+
+#[test]
+fn withdraw_takes_matured_tokens_first() {
+    let mut record: HodlingRecord<u64> = HodlingRecord::default();
+    // 20 matured tokens, 10 warming up with 100 weight accrued
+    record.last_weight = 1_000;
+    record.last_weight_update = 110;
+    record.start_warmup(10, 150);
+    record.warmups[0].weight = 100;
+
+    assert_eq!(record.forfeit_warmup(20, 30), 0);
+    assert_eq!(record.immature_amount(), 10);
+
+    // half of the immature amount goes, and half of its weight
+    assert_eq!(record.forfeit_warmup(15, 20), 50);
+    assert_eq!(record.immature_amount(), 5);
+    assert_eq!(record.immature_weight(), 50);
+    assert_eq!(record.last_weight, 950);
+}
+
+#[test]
+fn received_tokens_warm_up() {
+    with_externalities(&mut new_test_ext(), || {
+        <WarmupPeriodFor<Test>>::insert(sym(), 10);
+        reward(1_000);
+
+        System::set_block_number(1);
+        deposit(1, 100);
+        // moving the deposit right away must not skip the warm-up
+        System::set_block_number(2);
+        transfer(1, 2, 100);
+        assert_eq!(TokenStaking::hodling_record_for(&(1, sym())).last_weight, 0);
+        assert_eq!(
+            TokenStaking::hodling_record_for(&(2, sym())).next_maturity(),
+            Some(12)
+        );
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+
+        System::set_block_number(5);
+        assert_err!(
+            TokenStaking::claim(Origin::signed(2), sym()),
+            "token vote weight is still warming up."
+        );
+
+        System::set_block_number(12);
+        assert_ok!(TokenStaking::claim(Origin::signed(2), sym()));
+        assert_eq!(Balances::free_balance(&2), 1_000);
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+    });
+}
+
+#[test]
+fn warmup_keeps_maturity_per_deposit() {
+    let mut record: HodlingRecord<u64> = HodlingRecord::default();
+    record.last_weight_update = 100;
+    record.start_warmup(10, 150);
+
+    // a later deposit warms up on its own
+    record.settle_warmup(120);
+    record.last_weight = 10 * 20;
+    record.last_weight_update = 120;
+    record.start_warmup(5, 170);
+    assert_eq!(record.next_maturity(), Some(150));
+
+    // block 150: the first deposit matured, the second is still warming up
+    record.settle_warmup(150);
+    record.last_weight += 15 * 30;
+    record.last_weight_update = 150;
+    assert_eq!(record.immature_amount(), 5);
+    assert_eq!(record.immature_weight(), 5 * 30);
+    assert_eq!(record.mature_weight(), 10 * 50);
+    assert_eq!(record.next_maturity(), Some(170));
+
+    // the latest deposit forfeits first
+    assert_eq!(record.forfeit_warmup(15, 15), 150);
+    assert!(record.warmups.is_empty());
+}
+
+#[test]
+fn warmup_deposits_are_bounded() {
+    let mut record: HodlingRecord<u64> = HodlingRecord::default();
+    for i in 0..MAX_WARMUP_DEPOSITS as u64 + 5 {
+        record.start_warmup(1, 150 + i);
+    }
+    assert_eq!(record.warmups.len(), MAX_WARMUP_DEPOSITS);
+    assert_eq!(record.immature_amount(), MAX_WARMUP_DEPOSITS as u128 + 5);
+    // the first deposit keeps its maturity
+    assert_eq!(record.next_maturity(), Some(150));
+}
+
+#[test]
+fn received_dust_does_not_delay_deposit() {
+    with_externalities(&mut new_test_ext(), || {
+        <WarmupPeriodFor<Test>>::insert(sym(), 10);
+        reward(1_000);
+
+        System::set_block_number(1);
+        deposit(1, 100);
+        System::set_block_number(5);
+        deposit(3, 10);
+        transfer(3, 1, 1);
+
+        // the deposit of block 1 matures on time, the received token still warms up
+        System::set_block_number(11);
+        assert_ok!(TokenStaking::claim(Origin::signed(1), sym()));
+        assert!(Balances::free_balance(&1) > 0);
+        let record = TokenStaking::hodling_record_for(&(1, sym()));
+        assert_eq!(record.immature_amount(), 1);
+        assert_eq!(record.next_maturity(), Some(15));
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+    });
+}
+
+#[test]
+fn withdraw_forfeits_only_immature_share() {
+    with_externalities(&mut new_test_ext(), || {
+        <WarmupPeriodFor<Test>>::insert(sym(), 10);
+
+        System::set_block_number(1);
+        deposit(1, 100);
+        System::set_block_number(11);
+        deposit(1, 50);
+
+        // taken from the 100 matured tokens, the warm-up is untouched
+        System::set_block_number(14);
+        withdraw(1, 30);
+        let record = TokenStaking::hodling_record_for(&(1, sym()));
+        assert_eq!(record.immature_amount(), 50);
+        assert_eq!(record.immature_weight(), 150);
+        assert_eq!(record.last_weight, 1_450);
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+
+        // 70 matured and 30 immature tokens, 30 of 50 forfeits 3/5 of the immature weight
+        System::set_block_number(16);
+        withdraw(1, 100);
+        let record = TokenStaking::hodling_record_for(&(1, sym()));
+        assert_eq!(record.immature_amount(), 20);
+        assert_eq!(record.immature_weight(), 100);
+        assert_eq!(record.last_weight, 1_540);
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+
+        reward(1_000);
+        assert_ok!(TokenStaking::claim(Origin::signed(1), sym()));
+        assert!(Balances::free_balance(&1) > 0);
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+    });
+}
+
+#[test]
+fn legacy_hodling_record_decodes() {
+    // layout written before the warm-up period
+    let legacy = (1_000u128, 100u64).encode();
+    let record: HodlingRecord<u64> = Decode::decode(&mut legacy.as_slice()).unwrap();
+    assert_eq!(record.last_weight, 1_000);
+    assert_eq!(record.last_weight_update, 100);
+    assert_eq!(record.immature_amount(), 0);
+    assert_eq!(record.mature_weight(), 1_000);
+
+    let mut record = record;
+    record.start_warmup(10, 150);
+    let encoded = record.encode();
+    let decoded: HodlingRecord<u64> = Decode::decode(&mut encoded.as_slice()).unwrap();
+    assert!(decoded == record);
+}
+
+#[test]
+fn claim_all_charges_fee_only_when_settled() {
+    with_externalities(&mut new_test_ext(), || {
+        <Fee<Test>>::put(10);
+        <WarmupPeriodFor<Test>>::insert(sym(), 10);
+        reward(1_000);
+
+        System::set_block_number(1);
+        deposit(3, 100);
+
+        System::set_block_number(5);
+        assert_err!(
+            TokenStaking::claim_all(Origin::signed(3)),
+            "token vote weight is still warming up."
+        );
+        assert_err!(
+            TokenStaking::claim(Origin::signed(3), sym()),
+            "token vote weight is still warming up."
+        );
+        assert_eq!(Balances::free_balance(&3), 100);
+
+        System::set_block_number(11);
+        assert_ok!(TokenStaking::claim_all(Origin::signed(3)));
+        assert_eq!(Balances::free_balance(&3), 100 + 1_000 - 10);
+        assert_eq!(TokenStaking::hodling_record_for(&(3, sym())).last_weight, 0);
+    });
+}
+
+#[test]
+fn migration_indexes_existing_holders() {
+    with_externalities(&mut new_test_ext(), || {
+        use arml_support::migration::testing::{assert_migrated, set_storage_version};
+        use arml_support::migration::Migrate;
+        use runtime_support::storage;
+
+        // genesis is written with the current layout
+        assert_eq!(TokenStaking::storage_version(), 1);
+
+        // account 3 is known to balances, its record predates the warm-up and the holder index
+        storage::put(
+            &<HodlingRecordFor<Test>>::key_for(&(3, sym())),
+            &(1_000u128, 5u64),
+        );
+        set_storage_version::<TokenStaking>(0);
+
+        assert_migrated::<TokenStaking>();
+        assert_eq!(TokenStaking::holders_for(&sym()), vec![3]);
+        assert_eq!(TokenStaking::held_symbols_of(&3), vec![sym()]);
+        assert_eq!(
+            TokenStaking::hodling_record_for(&(3, sym())).last_weight,
+            1_000
+        );
+        // the runtime API view finds the record through the backfilled index
+        let records = TokenStaking::hodling_records_with_dividend(&3);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, sym());
+    });
+}
+
+#[test]
+fn session_stake_is_recorded_on_new_session_only() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(5);
+        // reading the stakes does not record them
+        assert_eq!(
+            TokenStaking::token_staking_info(),
+            vec![(Validator::Token(sym()), 0)]
+        );
+        assert_eq!(TokenStaking::last_session_stake_for(&sym()).updated_at, 0);
+
+        Session::check_rotate_session(System::block_number());
+        assert_eq!(TokenStaking::last_session_stake_for(&sym()).updated_at, 5);
+    });
+}
+
+/// xorshift, enough to drive random operation sequences without an extra dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+#[test]
+fn transfer_to_account_without_record_keeps_weight() {
+    with_externalities(&mut new_test_ext(), || {
+        System::set_block_number(1);
+        deposit(1, 100);
+        System::set_block_number(11);
+        transfer(1, 2, 40);
+        System::set_block_number(21);
+
+        assert_eq!(TokenStaking::total_hodling_weight(&sym()), 100 * 20);
+        assert_eq!(TokenStaking::total_virtual_weight(&sym()), 100 * 20);
+        assert_eq!(
+            TokenStaking::hodling_record_for(&(2, sym())).last_weight_update,
+            11
+        );
+    });
+}
+
+#[test]
+fn holder_without_tokens_and_weight_is_pruned() {
+    with_externalities(&mut new_test_ext(), || {
+        reward(1_000);
+        System::set_block_number(1);
+        deposit(1, 100);
+        deposit(2, 100);
+        assert_eq!(TokenStaking::holders_for(&sym()), vec![1, 2]);
+
+        // the weight accrued so far is still to be claimed
+        System::set_block_number(11);
+        withdraw(1, 100);
+        assert_eq!(TokenStaking::holders_count(&sym()), 2);
+
+        assert_ok!(TokenStaking::claim(Origin::signed(1), sym()));
+        assert_eq!(TokenStaking::holders_for(&sym()), vec![2]);
+        assert_eq!(TokenStaking::holders_count(&sym()), 1);
+        assert!(TokenStaking::held_symbols_of(&1).is_empty());
+        assert!(!HodlingRecordFor::<Test>::exists(&(1, sym())));
+        assert!(TokenStaking::weight_invariant_holds(&sym()));
+
+        // holding again starts a fresh record
+        deposit(1, 10);
+        assert_eq!(TokenStaking::holders_for(&sym()), vec![2, 1]);
+    });
+}
+
+#[test]
+fn hodling_weight_matches_total_weight_under_random_operations() {
+    for seed in 1..50u64 {
+        with_externalities(&mut new_test_ext(), || {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let accounts = 1 + rng.below(4);
+            <WarmupPeriodFor<Test>>::insert(sym(), rng.below(3) * 10);
+            reward(1_000_000);
+
+            let mut block = 1;
+            for _ in 0..100 {
+                block += rng.below(5);
+                System::set_block_number(block);
+
+                let who = 1 + rng.below(accounts);
+                let balance = TokenBalances::total_token_of(&who, &sym());
+                match rng.below(4) {
+                    0 => deposit(who, 1 + rng.below(1_000) as u128),
+                    1 if balance > 0 => withdraw(who, 1 + rng.below(balance as u64) as u128),
+                    2 if balance > 0 => {
+                        let to = 1 + rng.below(accounts);
+                        transfer(who, to, 1 + rng.below(balance as u64) as u128);
+                    }
+                    // a claim may fail while warming up, it must not leave anything behind
+                    _ => {
+                        let _ = TokenStaking::claim(Origin::signed(who), sym());
+                    }
+                }
+
+                assert!(
+                    TokenStaking::weight_invariant_holds(&sym()),
+                    "seed {}",
+                    seed
+                );
+                assert_eq!(
+                    TokenStaking::holders_for(&sym()).len() as u32,
+                    TokenStaking::holders_count(&sym())
+                );
+            }
+        });
+    }
+}
@@ -22,11 +22,13 @@ extern crate arml_associations as associations;
 extern crate arml_system;
 
 // use balances::EnsureAccountLiquid;
-use primitives::traits::{As, CheckedAdd, CheckedSub, OnFinalise, Zero};
+use primitives::traits::{As, CheckedAdd, CheckedSub, MakePayment, OnFinalise, Zero};
 use rstd::prelude::*;
 use runtime_support::dispatch::Result;
-pub use storage::double_map::StorageDoubleMap;
+use runtime_support::StorageValue;
+pub use storage::double_map::{EnumerableStorageDoubleMap, StorageDoubleMap};
 
+pub mod migration;
 pub mod storage;
 #[cfg(test)]
 mod tests;
@@ -36,9 +38,30 @@ decl_module! {
     }
 }
 
+decl_event!(
+    pub enum Event<T> where <T as balances::Trait>::Balance {
+        /// Fees burned in this block and the total burned so far.
+        Burned(Balance, Balance),
+    }
+);
+
+decl_storage! {
+    trait Store for Module<T: Trait> as CXSupport {
+        /// Fees paid to the death account, removed from the total issuance.
+        pub TotalBurned get(total_burned): T::Balance;
+        /// Fees burned in the current block.
+        pub BurnedInBlock get(burned_in_block): T::Balance;
+    }
+}
+
 impl<T: Trait> OnFinalise<T::BlockNumber> for Module<T> {
     fn on_finalise(_: T::BlockNumber) {
-        // do nothing
+        let burned = <BurnedInBlock<T>>::take();
+        if !burned.is_zero() {
+            let total = Self::total_burned() + burned;
+            <TotalBurned<T>>::put(total);
+            Self::deposit_event(RawEvent::Burned(burned, total));
+        }
     }
 }
 
@@ -48,9 +71,70 @@ impl<T: Trait> associations::OnCalcFee<T::AccountId, T::Balance> for Module<T> {
     }
 }
 
-pub trait Trait: associations::Trait + arml_system::Trait {}
+impl<T: Trait> balances::EnsureAccountLiquid<T::AccountId> for Module<T> {
+    fn ensure_account_liquid(who: &T::AccountId) -> Result {
+        Self::ensure_not_death_account(who)
+    }
+}
+
+/// Payment of the transaction fee, refused to the death account before any is charged.
+impl<T: Trait> MakePayment<T::AccountId> for Module<T> {
+    fn make_payment(transactor: &T::AccountId, encoded_len: usize) -> Result {
+        Self::ensure_not_death_account(transactor)?;
+        <balances::Module<T>>::make_payment(transactor, encoded_len)
+    }
+}
+
+pub trait Trait: associations::Trait + arml_system::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+/// Fee a call charges through `handle_fee_before` or `handle_fee_after`, so the transaction pool
+/// can order transactions by what they pay.
+pub trait CallFee<Balance> {
+    /// Fee of the call, zero if it charges none.
+    fn call_fee(&self) -> Balance;
+}
 
 impl<T: Trait> Module<T> {
+    /// Refuse `who` if it is the death account.
+    ///
+    /// The death account is `[0; 32]` on chain, a small order ed25519 point, so signatures for
+    /// it can be forged and it must not send anything.
+    pub fn ensure_not_death_account(who: &T::AccountId) -> Result {
+        if *who == arml_system::Module::<T>::death_account() {
+            Err("death account can not send transactions")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Deposit one of this module's events.
+    fn deposit_event(event: Event<T>) {
+        <system::Module<T>>::deposit_event(<T as Trait>::Event::from(event).into());
+    }
+
+    /// Credit `fee` to `to`, the share of the death account is burned instead.
+    fn pay_fee_to(to: &T::AccountId, fee: T::Balance) -> Result {
+        if *to == arml_system::Module::<T>::death_account() {
+            let issuance = <balances::Module<T>>::total_issuance()
+                .checked_sub(&fee)
+                .unwrap_or_else(Zero::zero);
+            <balances::TotalIssuance<T>>::put(issuance);
+            <BurnedInBlock<T>>::mutate(|b| *b += fee);
+            return Ok(());
+        }
+
+        let to_balance = <balances::Module<T>>::free_balance(to);
+        let new_to_balance = match to_balance.checked_add(&fee) {
+            Some(b) => b,
+            None => return Err("akro balance too high to exec this option"),
+        };
+        <balances::Module<T>>::set_free_balance(to, new_to_balance);
+        Ok(())
+    }
+
     fn calc_fee_withaccount(
         who: &T::AccountId,
         fee: T::Balance,
@@ -66,14 +150,8 @@ impl<T: Trait> Module<T> {
             panic!("can't input a empty rate array")
         }
         if rate.len() == 1 {
-            let to_balance = <balances::Module<T>>::free_balance(&rate[0].1);
-            let new_to_balance = match to_balance.checked_add(&fee) {
-                Some(b) => b,
-                None => return Err("akro balance too high to exec this option"),
-            };
-
+            Self::pay_fee_to(&rate[0].1, fee)?;
             <balances::Module<T>>::set_free_balance(who, new_from_balance);
-            <balances::Module<T>>::set_free_balance(&rate[0].1, new_to_balance);
             return Ok(());
         }
 
@@ -92,6 +170,10 @@ impl<T: Trait> Module<T> {
         v.insert(0, (fee - fee_sum, &rate[0].1));
 
         for (fee, accoundid) in v {
+            if *accoundid == arml_system::Module::<T>::death_account() {
+                Self::pay_fee_to(accoundid, fee)?;
+                continue;
+            }
             let to_balance = <balances::Module<T>>::free_balance(accoundid);
             let new_to_balance = match to_balance.checked_add(&fee) {
                 Some(b) => b,
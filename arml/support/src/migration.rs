@@ -0,0 +1,84 @@
+// Copyright 2018 Akropolis.
+
+//! Storage layout versioning for arml modules.
+//!
+//! A module implements `Migrate` and calls `on_runtime_upgrade` from `arml_system::OnBlockStart`,
+//! so the pending `migrate_step`s run once, before the first signed extrinsic dispatched by a
+//! runtime with a newer `STORAGE_VERSION`. Genesis should record the current version with
+//! `genesis_version`.
+
+use balances;
+use codec::Encode;
+use primitives::traits::As;
+use rstd::prelude::*;
+use runtime_io::twox_128;
+use runtime_support::storage;
+
+/// A module whose storage layout is versioned.
+pub trait Migrate {
+    /// Storage key of the layout version, e.g. `b"Staking StorageVersion"`.
+    const VERSION_KEY: &'static [u8];
+    /// Layout version the module code reads and writes.
+    const STORAGE_VERSION: u32;
+
+    /// Convert the storage from layout `from` to layout `from + 1`.
+    fn migrate_step(from: u32);
+
+    /// Layout version of the stored state, 0 for state written before versioning.
+    fn storage_version() -> u32 {
+        storage::get_or_default(Self::VERSION_KEY)
+    }
+
+    /// Run the pending migrations, returns whether there were any.
+    fn on_runtime_upgrade() -> bool {
+        let mut version = Self::storage_version();
+        if version >= Self::STORAGE_VERSION {
+            return false;
+        }
+        while version < Self::STORAGE_VERSION {
+            Self::migrate_step(version);
+            version += 1;
+        }
+        storage::put(Self::VERSION_KEY, &version);
+        true
+    }
+}
+
+/// Raw storage key and value recording the current layout version, for genesis builds.
+pub fn genesis_version<M: Migrate>() -> (Vec<u8>, Vec<u8>) {
+    (
+        twox_128(M::VERSION_KEY).to_vec(),
+        M::STORAGE_VERSION.encode(),
+    )
+}
+
+/// Every account known to balances, walking its account index sets.
+///
+/// Maps keyed by account can't be enumerated, a migration looks these accounts up in them.
+pub fn known_accounts<T: balances::Trait>() -> Vec<T::AccountId> {
+    let last: u64 = <balances::Module<T>>::next_enum_set().as_();
+    (0..=last)
+        .flat_map(|set| <balances::Module<T>>::enum_set(T::AccountIndex::sa(set)))
+        .collect()
+}
+
+/// Helpers for testing migrations against old format state.
+#[cfg(feature = "std")]
+pub mod testing {
+    use super::Migrate;
+    use runtime_support::storage;
+
+    /// Pretend the stored state was written with layout `version`.
+    pub fn set_storage_version<M: Migrate>(version: u32) {
+        storage::put(M::VERSION_KEY, &version);
+    }
+
+    /// Run the pending migrations of `M` and assert the state ends at the current layout.
+    pub fn assert_migrated<M: Migrate>() {
+        let from = M::storage_version();
+        assert_eq!(M::on_runtime_upgrade(), from < M::STORAGE_VERSION);
+        assert_eq!(M::storage_version(), M::STORAGE_VERSION);
+        // nothing left to run
+        assert_eq!(M::on_runtime_upgrade(), false);
+    }
+}
@@ -19,6 +19,7 @@
 //! This implementation is somewhat specialized to the tracking of the storage of accounts.
 
 use codec::{Codec, Encode};
+use rstd::marker::PhantomData;
 use rstd::prelude::*;
 use runtime_io::{blake2_256, twox_128};
 use runtime_support::storage::unhashed;
@@ -102,3 +103,222 @@ pub trait StorageDoubleMap {
         unhashed::take(&full_key::<Self>(k1, k2)[..])
     }
 }
+
+/// An implementation of a map with two keys, which can list the `Key2` entries under a `Key1`.
+///
+/// Values are stored under the same kind of keys as `StorageDoubleMap`. Besides, every `Key1`
+/// keeps a count of its entries and, for every entry, its `Key2` in a numbered slot and the
+/// number of that slot. Inserting or removing an entry touches a fixed number of keys, a
+/// removal moves the `Key2` of the last slot into the freed one.
+///
+/// The count and the slots are stored under a XX hash of the `PREFIX`, a tag and `Key1` (and
+/// the slot number), 16 bytes long, so they can't clash with a value key. The slot number of
+/// an entry is stored under a key built like the value key, with a tagged first part.
+pub trait EnumerableStorageDoubleMap {
+    type Key1: Codec + Clone;
+    type Key2: Codec + Clone + PartialEq;
+    type Value: Codec + Default;
+
+    const PREFIX: &'static [u8];
+
+    /// Storage key of the value under `k1` and `k2`.
+    fn key_for(k1: &Self::Key1, k2: &Self::Key2) -> Vec<u8> {
+        let mut raw_prefix = Vec::new();
+        raw_prefix.extend(Self::PREFIX);
+        raw_prefix.extend(Encode::encode(k1));
+
+        let mut k = Vec::new();
+        k.extend(&twox_128(&raw_prefix));
+        k.extend(&blake2_256(&Encode::encode(k2)));
+        k
+    }
+
+    /// Storage key of the number of entries under `k1`.
+    fn count_key_for(k1: &Self::Key1) -> Vec<u8> {
+        let mut raw_prefix = Vec::new();
+        raw_prefix.extend(Self::PREFIX);
+        raw_prefix.extend(b"count");
+        raw_prefix.extend(Encode::encode(k1));
+        twox_128(&raw_prefix).to_vec()
+    }
+
+    /// Storage key of the `Key2` in slot `slot` under `k1`.
+    fn slot_key_for(k1: &Self::Key1, slot: u32) -> Vec<u8> {
+        let mut raw_prefix = Vec::new();
+        raw_prefix.extend(Self::PREFIX);
+        raw_prefix.extend(b"slot");
+        raw_prefix.extend(Encode::encode(k1));
+        raw_prefix.extend(Encode::encode(&slot));
+        twox_128(&raw_prefix).to_vec()
+    }
+
+    /// Storage key of the slot number of `k2` under `k1`.
+    fn slot_of_key_for(k1: &Self::Key1, k2: &Self::Key2) -> Vec<u8> {
+        let mut raw_prefix = Vec::new();
+        raw_prefix.extend(Self::PREFIX);
+        raw_prefix.extend(b"slot_of");
+        raw_prefix.extend(Encode::encode(k1));
+
+        let mut k = Vec::new();
+        k.extend(&twox_128(&raw_prefix));
+        k.extend(&blake2_256(&Encode::encode(k2)));
+        k
+    }
+
+    /// Raw storage of `entries` under `k1`, in this order, for building genesis storage.
+    fn genesis_storage(
+        k1: &Self::Key1,
+        entries: Vec<(Self::Key2, Self::Value)>,
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut storage = Vec::new();
+        storage.push((Self::count_key_for(k1), (entries.len() as u32).encode()));
+        for (slot, (k2, val)) in entries.into_iter().enumerate() {
+            let slot = slot as u32;
+            storage.push((Self::slot_key_for(k1, slot), k2.encode()));
+            storage.push((Self::slot_of_key_for(k1, &k2), slot.encode()));
+            storage.push((Self::key_for(k1, &k2), val.encode()));
+        }
+        storage
+    }
+
+    /// All `Key2` with a value under `k1`, in slot order.
+    fn keys(k1: Self::Key1) -> Vec<Self::Key2> {
+        (0..Self::count(k1.clone()))
+            .filter_map(|slot| unhashed::get(&Self::slot_key_for(&k1, slot)[..]))
+            .collect()
+    }
+
+    /// Number of entries under `k1`.
+    fn count(k1: Self::Key1) -> u32 {
+        unhashed::get_or_default(&Self::count_key_for(&k1)[..])
+    }
+
+    /// Insert an entry into this map.
+    fn insert(k1: Self::Key1, k2: Self::Key2, val: Self::Value) {
+        let slot_of_key = Self::slot_of_key_for(&k1, &k2);
+        if unhashed::exists(&slot_of_key[..]) == false {
+            let count = Self::count(k1.clone());
+            unhashed::put(&Self::slot_key_for(&k1, count)[..], &k2);
+            unhashed::put(&slot_of_key[..], &count);
+            unhashed::put(&Self::count_key_for(&k1)[..], &(count + 1));
+        }
+        unhashed::put(&Self::key_for(&k1, &k2)[..], &val);
+    }
+
+    /// Remove an entry from this map.
+    fn remove(k1: Self::Key1, k2: Self::Key2) {
+        let slot_of_key = Self::slot_of_key_for(&k1, &k2);
+        if let Some(slot) = unhashed::get::<u32>(&slot_of_key[..]) {
+            let last = Self::count(k1.clone()).saturating_sub(1);
+            if slot != last {
+                // the last entry takes the freed slot
+                if let Some(moved) = unhashed::get::<Self::Key2>(&Self::slot_key_for(&k1, last)[..])
+                {
+                    unhashed::put(&Self::slot_key_for(&k1, slot)[..], &moved);
+                    unhashed::put(&Self::slot_of_key_for(&k1, &moved)[..], &slot);
+                }
+            }
+            unhashed::kill(&Self::slot_key_for(&k1, last)[..]);
+            unhashed::kill(&slot_of_key[..]);
+            if last == 0 {
+                unhashed::kill(&Self::count_key_for(&k1)[..]);
+            } else {
+                unhashed::put(&Self::count_key_for(&k1)[..], &last);
+            }
+        }
+        unhashed::kill(&Self::key_for(&k1, &k2)[..]);
+    }
+
+    /// Get an entry from this map.
+    ///
+    /// If there is no entry stored under the given keys, returns `None`.
+    fn get(k1: Self::Key1, k2: Self::Key2) -> Option<Self::Value> {
+        unhashed::get(&Self::key_for(&k1, &k2)[..])
+    }
+
+    /// Get an entry from this map.
+    ///
+    /// If there is no entry stored under the given keys, returns default value.
+    fn get_or_default(k1: Self::Key1, k2: Self::Key2) -> Self::Value {
+        unhashed::get_or_default(&Self::key_for(&k1, &k2)[..])
+    }
+
+    /// Whether an entry is stored under the given keys.
+    fn exists(k1: Self::Key1, k2: Self::Key2) -> bool {
+        unhashed::exists(&Self::key_for(&k1, &k2)[..])
+    }
+
+    /// Remove and return the value, None for the value not exist.
+    fn take(k1: Self::Key1, k2: Self::Key2) -> Option<Self::Value> {
+        let val = Self::get(k1.clone(), k2.clone());
+        if val.is_some() {
+            Self::remove(k1, k2);
+        }
+        val
+    }
+
+    /// Mutate the value under the given keys, a missing value starts from the default and
+    /// is inserted.
+    fn mutate<R, F: FnOnce(&mut Self::Value) -> R>(k1: Self::Key1, k2: Self::Key2, f: F) -> R {
+        let mut val = Self::get_or_default(k1.clone(), k2.clone());
+        let r = f(&mut val);
+        Self::insert(k1, k2, val);
+        r
+    }
+
+    /// Removes all entries that shares the `k1` as the first key, together with their slots.
+    fn remove_prefix(k1: Self::Key1) {
+        for slot in 0..Self::count(k1.clone()) {
+            unhashed::kill(&Self::slot_key_for(&k1, slot)[..]);
+        }
+        unhashed::kill(&Self::count_key_for(&k1)[..]);
+
+        let mut raw_prefix = Vec::new();
+        raw_prefix.extend(Self::PREFIX);
+        raw_prefix.extend(Encode::encode(&k1));
+        unhashed::kill_prefix(&twox_128(&raw_prefix));
+
+        let mut raw_prefix = Vec::new();
+        raw_prefix.extend(Self::PREFIX);
+        raw_prefix.extend(b"slot_of");
+        raw_prefix.extend(Encode::encode(&k1));
+        unhashed::kill_prefix(&twox_128(&raw_prefix));
+    }
+
+    /// Iterate the `(Key2, Value)` entries under `k1` in slot order.
+    ///
+    /// Slots and values are read lazily, the map must not be modified while iterating.
+    fn iter_prefix(k1: Self::Key1) -> PrefixIter<Self> {
+        PrefixIter {
+            slot: 0,
+            count: Self::count(k1.clone()),
+            k1,
+            _map: PhantomData,
+        }
+    }
+}
+
+/// Iterator over the entries under one `Key1` of an `EnumerableStorageDoubleMap`.
+pub struct PrefixIter<M: EnumerableStorageDoubleMap + ?Sized> {
+    k1: M::Key1,
+    slot: u32,
+    count: u32,
+    _map: PhantomData<M>,
+}
+
+impl<M: EnumerableStorageDoubleMap + ?Sized> Iterator for PrefixIter<M> {
+    type Item = (M::Key2, M::Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.slot < self.count {
+            let slot_key = M::slot_key_for(&self.k1, self.slot);
+            self.slot += 1;
+            if let Some(k2) = unhashed::get::<M::Key2>(&slot_key[..]) {
+                if let Some(val) = M::get(self.k1.clone(), k2.clone()) {
+                    return Some((k2, val));
+                }
+            }
+        }
+        None
+    }
+}
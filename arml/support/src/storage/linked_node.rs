@@ -1,6 +1,8 @@
 // Copyright 2018 Akropolis.
 
 use codec::Codec;
+use rstd::marker::PhantomData;
+use rstd::prelude::*;
 use runtime_support::dispatch::Result;
 use runtime_support::{StorageMap, StorageValue};
 
@@ -176,10 +178,107 @@ where
     }
 }
 
+/// Unwrap the value of a `NodeMap` query, whether the map is declared with or without `Option`.
+pub trait IntoNode<T: NodeT> {
+    fn into_node(self) -> Option<Node<T>>;
+}
+
+impl<T: NodeT> IntoNode<T> for Node<T> {
+    fn into_node(self) -> Option<Node<T>> {
+        Some(self)
+    }
+}
+
+impl<T: NodeT> IntoNode<T> for Option<Node<T>> {
+    fn into_node(self) -> Option<Node<T>> {
+        self
+    }
+}
+
+/// Unwrap the index of a `Header`/`Tail` query, whether it is declared with or without `Option`.
+pub trait IntoNodeIndex<I> {
+    fn into_index(self) -> Option<I>;
+}
+
+impl<T: NodeT> IntoNodeIndex<T::Index> for NodeIndex<T> {
+    fn into_index(self) -> Option<T::Index> {
+        Some(self.index)
+    }
+}
+
+impl<T: NodeT> IntoNodeIndex<T::Index> for Option<NodeIndex<T>> {
+    fn into_index(self) -> Option<T::Index> {
+        self.map(|i| i.index)
+    }
+}
+
+impl<K, T: NodeT> IntoNodeIndex<T::Index> for MultiNodeIndex<K, T>
+where
+    K: Codec + Clone + Eq + PartialEq + Default,
+{
+    fn into_index(self) -> Option<T::Index> {
+        Some(self.index)
+    }
+}
+
+impl<K, T: NodeT> IntoNodeIndex<T::Index> for Option<MultiNodeIndex<K, T>>
+where
+    K: Codec + Clone + Eq + PartialEq + Default,
+{
+    fn into_index(self) -> Option<T::Index> {
+        self.map(|i| i.index)
+    }
+}
+
+/// `Length` is a `u32` `StorageValue` for the plain collections, and a `u32` `StorageMap`
+/// from the multi key for the `_withkey` collections.
 pub trait LinkedNodeCollection {
     type Header;
     type NodeMap;
     type Tail;
+    type Length;
+}
+
+/// Iterator over the nodes of a `LinkedNodeCollection`, following `next` or, reversed, `prev`.
+///
+/// Nodes are read from storage lazily, so the collection must not be modified while iterating.
+pub struct LinkedNodeIter<C: LinkedNodeCollection, T: NodeT> {
+    index: Option<T::Index>,
+    reverse: bool,
+    _collection: PhantomData<C>,
+}
+
+impl<C: LinkedNodeCollection, T: NodeT> LinkedNodeIter<C, T> {
+    /// Iterate from the node at `index`, `None` gives an empty iterator.
+    pub fn new(index: Option<T::Index>, reverse: bool) -> Self {
+        LinkedNodeIter {
+            index,
+            reverse,
+            _collection: PhantomData,
+        }
+    }
+}
+
+impl<C: LinkedNodeCollection, T: NodeT + Codec> Iterator for LinkedNodeIter<C, T>
+where
+    C::NodeMap: StorageMap<T::Index, Node<T>>,
+    <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query: IntoNode<T>,
+{
+    type Item = Node<T>;
+
+    fn next(&mut self) -> Option<Node<T>> {
+        let index = self.index.take()?;
+        if C::NodeMap::exists(&index) == false {
+            return None;
+        }
+        let node = C::NodeMap::get(&index).into_node()?;
+        self.index = if self.reverse {
+            node.prev()
+        } else {
+            node.next()
+        };
+        Some(node)
+    }
 }
 
 impl<T: NodeT + Codec> Node<T> {
@@ -195,6 +294,7 @@ impl<T: NodeT + Codec> Node<T> {
     where
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Header: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
         <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
             OptionT<OptionType = Node<T>>,
     {
@@ -202,6 +302,7 @@ impl<T: NodeT + Codec> Node<T> {
         if i == node.index() {
             return Ok(());
         }
+        let is_new = C::NodeMap::exists(&node.index()) == false;
         match &self.prev {
             Some(p) => {
                 C::NodeMap::mutate(p, |prev| {
@@ -230,6 +331,9 @@ impl<T: NodeT + Codec> Node<T> {
 
         let i = node.index();
         C::NodeMap::insert(i, node);
+        if is_new {
+            C::Length::mutate(|l| *l += 1);
+        }
         Ok(())
     }
 
@@ -237,6 +341,7 @@ impl<T: NodeT + Codec> Node<T> {
     where
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
         <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
             OptionT<OptionType = Node<T>>,
     {
@@ -244,6 +349,7 @@ impl<T: NodeT + Codec> Node<T> {
         if i == node.index() {
             return Ok(());
         }
+        let is_new = C::NodeMap::exists(&node.index()) == false;
         match &self.next {
             Some(n) => {
                 C::NodeMap::mutate(n, |next| {
@@ -270,10 +376,33 @@ impl<T: NodeT + Codec> Node<T> {
         C::NodeMap::insert(self.index(), self);
         let i = node.index();
         C::NodeMap::insert(i, node);
+        if is_new {
+            C::Length::mutate(|l| *l += 1);
+        }
         Ok(())
     }
 
     pub fn remove_option_node<C: LinkedNodeCollection>(&mut self) -> Result
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+    {
+        let self_index = self.index();
+        let existed = C::NodeMap::exists(&self_index);
+        let r = self.unlink_option_node::<C>();
+        if existed && C::NodeMap::exists(&self_index) == false {
+            C::Length::mutate(|l| *l = l.saturating_sub(1));
+        }
+        r
+    }
+
+    fn unlink_option_node<C: LinkedNodeCollection>(&mut self) -> Result
     where
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Header: StorageValue<NodeIndex<T>>,
@@ -365,6 +494,7 @@ impl<T: NodeT + Codec> Node<T> {
     where
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Header: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
         <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
             NormalNodeT<NodeType = Node<T>>,
     {
@@ -372,6 +502,7 @@ impl<T: NodeT + Codec> Node<T> {
         if i == node.index() {
             return Ok(());
         }
+        let is_new = C::NodeMap::exists(&node.index()) == false;
         match &self.prev {
             Some(p) => C::NodeMap::mutate(p, |prev_node| {
                 if prev_node.data().is_none() == false {
@@ -398,6 +529,9 @@ impl<T: NodeT + Codec> Node<T> {
 
         let i = node.index();
         C::NodeMap::insert(i, node);
+        if is_new {
+            C::Length::mutate(|l| *l += 1);
+        }
         Ok(())
     }
 
@@ -405,6 +539,7 @@ impl<T: NodeT + Codec> Node<T> {
     where
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
         <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
             NormalNodeT<NodeType = Node<T>>,
     {
@@ -412,6 +547,7 @@ impl<T: NodeT + Codec> Node<T> {
         if i == node.index() {
             return Ok(());
         }
+        let is_new = C::NodeMap::exists(&node.index()) == false;
         match &self.next {
             Some(n) => C::NodeMap::mutate(n, |next_node| {
                 if next_node.data().is_none() == false {
@@ -435,10 +571,33 @@ impl<T: NodeT + Codec> Node<T> {
         C::NodeMap::insert(self.index(), self);
         let i = node.index();
         C::NodeMap::insert(i, node);
+        if is_new {
+            C::Length::mutate(|l| *l += 1);
+        }
         Ok(())
     }
 
     pub fn remove_node<C: LinkedNodeCollection>(&mut self) -> Result
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            NormalNodeT<NodeType = Node<T>>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: NodeIndexT<IndexType = NodeIndex<T>>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: NodeIndexT<IndexType = NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+    {
+        let self_index = self.index();
+        let existed = C::NodeMap::exists(&self_index);
+        let r = self.unlink_node::<C>();
+        if existed && C::NodeMap::exists(&self_index) == false {
+            C::Length::mutate(|l| *l = l.saturating_sub(1));
+        }
+        r
+    }
+
+    fn unlink_node<C: LinkedNodeCollection>(&mut self) -> Result
     where
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Header: StorageValue<NodeIndex<T>>,
@@ -526,7 +685,11 @@ impl<T: NodeT + Codec> Node<T> {
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Header: StorageValue<NodeIndex<T>>,
         C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
     {
+        if C::NodeMap::exists(&self.index()) == false {
+            C::Length::mutate(|l| *l += 1);
+        }
         if C::Header::exists() == false {
             C::Header::put(NodeIndex::<T> {
                 index: self.index(),
@@ -541,6 +704,64 @@ impl<T: NodeT + Codec> Node<T> {
     }
 }
 
+// iteration, length and clear
+impl<T: NodeT + Codec> Node<T> {
+    /// Iterate the collection from header to tail.
+    pub fn iter<C: LinkedNodeCollection>() -> LinkedNodeIter<C, T>
+    where
+        C::Header: StorageValue<NodeIndex<T>>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let header = if C::Header::exists() {
+            C::Header::get().into_index()
+        } else {
+            None
+        };
+        LinkedNodeIter::new(header, false)
+    }
+
+    /// Iterate the collection from tail to header.
+    pub fn iter_rev<C: LinkedNodeCollection>() -> LinkedNodeIter<C, T>
+    where
+        C::Tail: StorageValue<NodeIndex<T>>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let tail = if C::Tail::exists() {
+            C::Tail::get().into_index()
+        } else {
+            None
+        };
+        LinkedNodeIter::new(tail, true)
+    }
+
+    /// Number of nodes in the collection.
+    pub fn len<C: LinkedNodeCollection>() -> u32
+    where
+        C::Length: StorageValue<u32, Query = u32>,
+    {
+        C::Length::get()
+    }
+
+    /// Remove every node of the collection, together with its header, tail and length.
+    pub fn clear<C: LinkedNodeCollection>()
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query: IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let indexes: Vec<T::Index> = Self::iter::<C>().map(|node| node.index()).collect();
+        for index in indexes {
+            C::NodeMap::remove(&index);
+        }
+        C::Header::kill();
+        C::Tail::kill();
+        C::Length::kill();
+    }
+}
+
 // for multi index
 impl<T: NodeT + Codec> Node<T> {
     pub fn init_storage_withkey<C: LinkedNodeCollection, K>(&self, key: K)
@@ -551,7 +772,11 @@ impl<T: NodeT + Codec> Node<T> {
             StorageMap<<MultiNodeIndex<K, T> as MultiNodeIndexT>::KeyType, MultiNodeIndex<K, T>>,
         C::Tail:
             StorageMap<<MultiNodeIndex<K, T> as MultiNodeIndexT>::KeyType, MultiNodeIndex<K, T>>,
+        C::Length: StorageMap<K, u32, Query = u32>,
     {
+        if C::NodeMap::exists(&self.index()) == false {
+            C::Length::mutate(&key, |l| *l += 1);
+        }
         if C::Header::exists(&key) == false {
             C::Header::insert(
                 key.clone(),
@@ -583,6 +808,7 @@ impl<T: NodeT + Codec> Node<T> {
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Header:
             StorageMap<<MultiNodeIndex<K, T> as MultiNodeIndexT>::KeyType, MultiNodeIndex<K, T>>,
+        C::Length: StorageMap<K, u32, Query = u32>,
         <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
             OptionT<OptionType = Node<T>>,
     {
@@ -590,6 +816,7 @@ impl<T: NodeT + Codec> Node<T> {
         if i == node.index() {
             return Ok(());
         }
+        let is_new = C::NodeMap::exists(&node.index()) == false;
         match &self.prev {
             Some(p) => {
                 C::NodeMap::mutate(p, |prev| {
@@ -608,7 +835,7 @@ impl<T: NodeT + Codec> Node<T> {
                     key.clone(),
                     MultiNodeIndex::<K, T> {
                         index: node.index(),
-                        multi_key: key,
+                        multi_key: key.clone(),
                     },
                 );
             }
@@ -622,6 +849,9 @@ impl<T: NodeT + Codec> Node<T> {
 
         let i = node.index();
         C::NodeMap::insert(i, node);
+        if is_new {
+            C::Length::mutate(&key, |l| *l += 1);
+        }
         Ok(())
     }
 
@@ -635,6 +865,7 @@ impl<T: NodeT + Codec> Node<T> {
         C::NodeMap: StorageMap<T::Index, Node<T>>,
         C::Tail:
             StorageMap<<MultiNodeIndex<K, T> as MultiNodeIndexT>::KeyType, MultiNodeIndex<K, T>>,
+        C::Length: StorageMap<K, u32, Query = u32>,
         <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
             OptionT<OptionType = Node<T>>,
     {
@@ -642,6 +873,7 @@ impl<T: NodeT + Codec> Node<T> {
         if i == node.index() {
             return Ok(());
         }
+        let is_new = C::NodeMap::exists(&node.index()) == false;
         match &self.next {
             Some(n) => {
                 C::NodeMap::mutate(n, |next| {
@@ -660,7 +892,7 @@ impl<T: NodeT + Codec> Node<T> {
                     key.clone(),
                     MultiNodeIndex::<K, T> {
                         index: node.index(),
-                        multi_key: key,
+                        multi_key: key.clone(),
                     },
                 );
             }
@@ -672,10 +904,36 @@ impl<T: NodeT + Codec> Node<T> {
         C::NodeMap::insert(self.index(), self);
         let i = node.index();
         C::NodeMap::insert(i, node);
+        if is_new {
+            C::Length::mutate(&key, |l| *l += 1);
+        }
         Ok(())
     }
 
     pub fn remove_option_node_withkey<C: LinkedNodeCollection, K>(&mut self, key: K) -> Result
+    where
+        K: Codec + Clone + Eq + PartialEq + Default,
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageMap<K, MultiNodeIndex<K, T>>,
+        C::Tail: StorageMap<K, MultiNodeIndex<K, T>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>>,
+        <C::Header as StorageMap<K, MultiNodeIndex<K, T>>>::Query:
+            OptionT<OptionType = MultiNodeIndex<K, T>>,
+        <C::Tail as StorageMap<K, MultiNodeIndex<K, T>>>::Query:
+            OptionT<OptionType = MultiNodeIndex<K, T>>,
+        C::Length: StorageMap<K, u32, Query = u32>,
+    {
+        let self_index = self.index();
+        let existed = C::NodeMap::exists(&self_index);
+        let r = self.unlink_option_node_withkey::<C, K>(key.clone());
+        if existed && C::NodeMap::exists(&self_index) == false {
+            C::Length::mutate(&key, |l| *l = l.saturating_sub(1));
+        }
+        r
+    }
+
+    fn unlink_option_node_withkey<C: LinkedNodeCollection, K>(&mut self, key: K) -> Result
     where
         K: Codec + Clone + Eq + PartialEq + Default,
         C::NodeMap: StorageMap<T::Index, Node<T>>,
@@ -773,4 +1031,65 @@ impl<T: NodeT + Codec> Node<T> {
         }
         Ok(())
     }
+
+    /// Iterate the nodes under `key` from header to tail.
+    pub fn iter_withkey<C: LinkedNodeCollection, K>(key: &K) -> LinkedNodeIter<C, T>
+    where
+        K: Codec + Clone + Eq + PartialEq + Default,
+        C::Header: StorageMap<K, MultiNodeIndex<K, T>>,
+        <C::Header as StorageMap<K, MultiNodeIndex<K, T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let header = if C::Header::exists(key) {
+            C::Header::get(key).into_index()
+        } else {
+            None
+        };
+        LinkedNodeIter::new(header, false)
+    }
+
+    /// Iterate the nodes under `key` from tail to header.
+    pub fn iter_rev_withkey<C: LinkedNodeCollection, K>(key: &K) -> LinkedNodeIter<C, T>
+    where
+        K: Codec + Clone + Eq + PartialEq + Default,
+        C::Tail: StorageMap<K, MultiNodeIndex<K, T>>,
+        <C::Tail as StorageMap<K, MultiNodeIndex<K, T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let tail = if C::Tail::exists(key) {
+            C::Tail::get(key).into_index()
+        } else {
+            None
+        };
+        LinkedNodeIter::new(tail, true)
+    }
+
+    /// Number of nodes under `key`.
+    pub fn len_withkey<C: LinkedNodeCollection, K>(key: &K) -> u32
+    where
+        K: Codec + Clone + Eq + PartialEq + Default,
+        C::Length: StorageMap<K, u32, Query = u32>,
+    {
+        C::Length::get(key)
+    }
+
+    /// Remove every node under `key`, together with its header, tail and length.
+    pub fn clear_withkey<C: LinkedNodeCollection, K>(key: &K)
+    where
+        K: Codec + Clone + Eq + PartialEq + Default,
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageMap<K, MultiNodeIndex<K, T>>,
+        C::Tail: StorageMap<K, MultiNodeIndex<K, T>>,
+        C::Length: StorageMap<K, u32, Query = u32>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query: IntoNode<T>,
+        <C::Header as StorageMap<K, MultiNodeIndex<K, T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let indexes: Vec<T::Index> = Self::iter_withkey::<C, K>(key)
+            .map(|node| node.index())
+            .collect();
+        for index in indexes {
+            C::NodeMap::remove(&index);
+        }
+        C::Header::remove(key);
+        C::Tail::remove(key);
+        C::Length::remove(key);
+    }
 }
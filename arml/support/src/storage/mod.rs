@@ -3,3 +3,4 @@
 pub mod btree_map;
 pub mod double_map;
 pub mod linked_node;
+pub mod ordered_list;
@@ -0,0 +1,242 @@
+// Copyright 2018 Akropolis.
+
+//! Sorted linked node collections, for storage backed priority queues such as order books.
+//!
+//! Only the Option mode collections are supported, the same as the `*_option_node*` calls.
+//! Nodes comparing equal keep their insertion order.
+
+use codec::Codec;
+use rstd::cmp::Ordering;
+use rstd::prelude::*;
+use runtime_support::dispatch::Result;
+use runtime_support::{StorageMap, StorageValue};
+
+use super::linked_node::{
+    IntoNode, IntoNodeIndex, LinkedNodeCollection, LinkedNodeIter, Node, NodeIndex, NodeT, OptionT,
+};
+
+/// A sorted collection with a skip index, bounding the number of nodes an insertion walks.
+///
+/// Nodes must leave it through `remove_indexed`, `pop_front_indexed` or `pop_back_indexed`,
+/// which drop their checkpoints.
+pub trait SkipIndexedCollection: LinkedNodeCollection {
+    /// `StorageValue` of `Vec<Index>`, checkpoint nodes in list order.
+    type SkipIndex;
+    /// Segment length the checkpoints are spaced at. An insertion splits its segment once it
+    /// grows past twice this length.
+    const SKIP_SPAN: u32;
+}
+
+impl<T: NodeT + Codec> Node<T> {
+    /// Insert this node in front of the first node `cmp` orders after it.
+    pub fn insert_sorted<C: LinkedNodeCollection, F>(self, cmp: F) -> Result
+    where
+        F: Fn(&T, &T) -> Ordering,
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let header = Self::iter::<C>().next().map(|n| n.index());
+        self.insert_sorted_from::<C, F>(header, &cmp)
+    }
+
+    /// Like `insert_sorted`, starting the walk from the last checkpoint of the skip index
+    /// ordered before this node.
+    pub fn insert_sorted_indexed<C: SkipIndexedCollection, F>(self, cmp: F) -> Result
+    where
+        F: Fn(&T, &T) -> Ordering,
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        C::SkipIndex: StorageValue<Vec<T::Index>, Query = Vec<T::Index>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        let mut checkpoints = C::SkipIndex::get();
+
+        // number of checkpoints ordered before or equal to this node
+        let (mut low, mut high) = (0, checkpoints.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            let after = match C::NodeMap::get(&checkpoints[mid]).into_node() {
+                Some(n) => cmp(&self.data, &n.data) == Ordering::Less,
+                None => true,
+            };
+            if after {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        let start = if low == 0 {
+            Self::iter::<C>().next().map(|n| n.index())
+        } else {
+            Some(checkpoints[low - 1].clone())
+        };
+        self.insert_sorted_from::<C, F>(start.clone(), &cmp)?;
+
+        // the segment the node went into, it may be the new header
+        let start = if low == 0 {
+            Self::iter::<C>().next().map(|n| n.index())
+        } else {
+            start
+        };
+        let end = checkpoints.get(low).cloned();
+        let segment: Vec<T::Index> = LinkedNodeIter::<C, T>::new(start, false)
+            .map(|n| n.index())
+            .take_while(|i| Some(i) != end.as_ref())
+            .collect();
+
+        // split it into segments of `SKIP_SPAN` nodes, this also splits the longer segments
+        // left by removed checkpoints
+        let span = C::SKIP_SPAN.max(1) as usize;
+        if segment.len() > 2 * span {
+            let splits: Vec<T::Index> = segment.into_iter().skip(span).step_by(span).collect();
+            for (n, index) in splits.into_iter().enumerate() {
+                checkpoints.insert(low + n, index);
+            }
+            C::SkipIndex::put(checkpoints);
+        }
+        Ok(())
+    }
+
+    /// Remove this node from a skip indexed collection, together with its checkpoint.
+    pub fn remove_indexed<C: SkipIndexedCollection>(&mut self) -> Result
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        C::SkipIndex: StorageValue<Vec<T::Index>, Query = Vec<T::Index>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+    {
+        self.remove_option_node::<C>()?;
+        // a stale checkpoint would be revived by the index being inserted again
+        let index = self.index();
+        let mut checkpoints = C::SkipIndex::get();
+        if let Some(pos) = checkpoints.iter().position(|i| *i == index) {
+            checkpoints.remove(pos);
+            C::SkipIndex::put(checkpoints);
+        }
+        Ok(())
+    }
+
+    /// Like `pop_front`, for a skip indexed collection.
+    pub fn pop_front_indexed<C: SkipIndexedCollection>() -> Option<T>
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        C::SkipIndex: StorageValue<Vec<T::Index>, Query = Vec<T::Index>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query:
+            OptionT<OptionType = NodeIndex<T>> + IntoNodeIndex<T::Index>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+    {
+        let mut node = Self::iter::<C>().next()?;
+        node.remove_indexed::<C>().ok()?;
+        Some(node.data)
+    }
+
+    /// Like `pop_back`, for a skip indexed collection.
+    pub fn pop_back_indexed<C: SkipIndexedCollection>() -> Option<T>
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        C::SkipIndex: StorageValue<Vec<T::Index>, Query = Vec<T::Index>>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query:
+            OptionT<OptionType = NodeIndex<T>> + IntoNodeIndex<T::Index>,
+    {
+        let mut node = Self::iter_rev::<C>().next()?;
+        node.remove_indexed::<C>().ok()?;
+        Some(node.data)
+    }
+
+    /// Walk from `start` and link this node in front of the first node ordered after it.
+    fn insert_sorted_from<C: LinkedNodeCollection, F>(
+        self,
+        start: Option<T::Index>,
+        cmp: &F,
+    ) -> Result
+    where
+        F: Fn(&T, &T) -> Ordering,
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: IntoNodeIndex<T::Index>,
+    {
+        if C::NodeMap::exists(&self.index()) {
+            return Err("node is already in the list");
+        }
+
+        for mut n in LinkedNodeIter::<C, T>::new(start, false) {
+            if cmp(&self.data, &n.data) == Ordering::Less {
+                return n.add_option_node_before::<C>(self);
+            }
+        }
+
+        match Self::iter_rev::<C>().next() {
+            Some(mut tail) => tail.add_option_node_after::<C>(self)?,
+            None => self.init_storage::<C>(),
+        }
+        Ok(())
+    }
+
+    /// Remove the first node and return its data.
+    pub fn pop_front<C: LinkedNodeCollection>() -> Option<T>
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query:
+            OptionT<OptionType = NodeIndex<T>> + IntoNodeIndex<T::Index>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+    {
+        let mut node = Self::iter::<C>().next()?;
+        node.remove_option_node::<C>().ok()?;
+        Some(node.data)
+    }
+
+    /// Remove the last node and return its data.
+    pub fn pop_back<C: LinkedNodeCollection>() -> Option<T>
+    where
+        C::NodeMap: StorageMap<T::Index, Node<T>>,
+        C::Header: StorageValue<NodeIndex<T>>,
+        C::Tail: StorageValue<NodeIndex<T>>,
+        C::Length: StorageValue<u32, Query = u32>,
+        <C::NodeMap as StorageMap<<T as NodeT>::Index, Node<T>>>::Query:
+            OptionT<OptionType = Node<T>> + IntoNode<T>,
+        <C::Header as StorageValue<NodeIndex<T>>>::Query: OptionT<OptionType = NodeIndex<T>>,
+        <C::Tail as StorageValue<NodeIndex<T>>>::Query:
+            OptionT<OptionType = NodeIndex<T>> + IntoNodeIndex<T::Index>,
+    {
+        let mut node = Self::iter_rev::<C>().next()?;
+        node.remove_option_node::<C>().ok()?;
+        Some(node.data)
+    }
+}
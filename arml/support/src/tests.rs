@@ -38,14 +38,18 @@ impl balances::Trait for Test {
     type Event = ();
 }
 
-impl arml_system::Trait for Test {}
+impl arml_system::Trait for Test {
+    type OnBlockStart = ();
+}
 
 impl associations::Trait for Test {
     type OnCalcFee = CXSupport;
     type Event = ();
 }
 
-impl Trait for Test {}
+impl Trait for Test {
+    type Event = ();
+}
 
 type Balances = balances::Module<Test>;
 type CXSystem = arml_system::Module<Test>;
@@ -71,9 +75,12 @@ pub fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
     );
     // arml_system
     r.extend(
-        arml_system::GenesisConfig::<Test> { death_account: 100 }
-            .build_storage()
-            .unwrap(),
+        arml_system::GenesisConfig::<Test> {
+            death_account: 100,
+            fee_buy_account: 101,
+        }
+        .build_storage()
+        .unwrap(),
     );
 
     r.into()
@@ -84,7 +91,59 @@ fn test_no_relation_no_producer() {
     with_externalities(&mut new_test_ext(), || {
         assert_ok!(CXSupport::handle_fee_before(&1, 100, true, || Ok(())));
 
-        assert_eq!(Balances::free_balance(CXSystem::death_account()), 100);
+        // burned instead of credited to the death account
+        assert_eq!(Balances::free_balance(CXSystem::death_account()), 0);
+        assert_eq!(Balances::free_balance(1), 900);
+        assert_eq!(Balances::total_issuance(), 2510 - 100);
+        assert_eq!(CXSupport::burned_in_block(), 100);
+        assert_eq!(CXSupport::total_burned(), 0);
+
+        CXSupport::on_finalise(1);
+        assert_eq!(CXSupport::burned_in_block(), 0);
+        assert_eq!(CXSupport::total_burned(), 100);
+
+        assert_ok!(CXSupport::handle_fee_before(&3, 50, true, || Ok(())));
+        CXSupport::on_finalise(2);
+        assert_eq!(CXSupport::total_burned(), 150);
+        assert_eq!(Balances::total_issuance(), 2510 - 150);
+    })
+}
+
+#[test]
+fn test_burn_relation_share_only() {
+    with_externalities(&mut new_test_ext(), || {
+        let origin = system::RawOrigin::Signed(1).into();
+        assert_ok!(associations::Module::<Test>::init_account(origin, 10, 100));
+
+        // no producer, the producer half is burned and the referrer half is paid
+        assert_ok!(CXSupport::handle_fee_before(&10, 99, true, || Ok(())));
+        assert_eq!(Balances::free_balance(1), 1000 - 100 + 49);
+        assert_eq!(CXSupport::burned_in_block(), 50);
+        assert_eq!(Balances::total_issuance(), 2510 - 50);
+    })
+}
+
+#[test]
+fn test_death_account_can_not_sign() {
+    with_externalities(&mut new_test_ext(), || {
+        use balances::EnsureAccountLiquid;
+
+        assert!(CXSupport::ensure_account_liquid(&CXSystem::death_account()).is_err());
+        assert_ok!(CXSupport::ensure_account_liquid(&1));
+    })
+}
+
+#[test]
+fn test_death_account_can_not_pay_for_extrinsics() {
+    with_externalities(&mut new_test_ext(), || {
+        use primitives::traits::MakePayment;
+
+        // signed extrinsics pay before they are dispatched
+        assert_err!(
+            CXSupport::make_payment(&CXSystem::death_account(), 100),
+            "death account can not send transactions"
+        );
+        assert_ok!(CXSupport::make_payment(&1, 100));
     })
 }
 
@@ -118,3 +177,171 @@ fn test_with_relation_with_producer() {
         assert_eq!(Balances::free_balance(10), 100 - 99);
     })
 }
+
+struct TestDoubleMap;
+
+impl EnumerableStorageDoubleMap for TestDoubleMap {
+    type Key1 = u64;
+    type Key2 = u64;
+    type Value = u32;
+
+    const PREFIX: &'static [u8] = b"CXSupport TestDoubleMap";
+}
+
+#[test]
+fn test_double_map_iter_prefix() {
+    with_externalities(&mut new_test_ext(), || {
+        TestDoubleMap::insert(1, 20, 200);
+        TestDoubleMap::insert(1, 10, 100);
+        TestDoubleMap::insert(2, 10, 1);
+        // overwrite keeps the position
+        TestDoubleMap::insert(1, 20, 201);
+
+        assert_eq!(TestDoubleMap::count(1), 2);
+        assert_eq!(TestDoubleMap::count(2), 1);
+        assert_eq!(TestDoubleMap::count(3), 0);
+        assert_eq!(TestDoubleMap::keys(1), vec![20, 10]);
+        assert_eq!(
+            TestDoubleMap::iter_prefix(1).collect::<Vec<_>>(),
+            vec![(20, 201), (10, 100)]
+        );
+        assert_eq!(TestDoubleMap::iter_prefix(3).next(), None);
+
+        assert_eq!(TestDoubleMap::take(1, 20), Some(201));
+        assert_eq!(TestDoubleMap::take(1, 20), None);
+        assert_eq!(TestDoubleMap::exists(1, 20), false);
+        assert_eq!(
+            TestDoubleMap::iter_prefix(1).collect::<Vec<_>>(),
+            vec![(10, 100)]
+        );
+
+        TestDoubleMap::remove(1, 10);
+        assert_eq!(TestDoubleMap::count(1), 0);
+        assert_eq!(TestDoubleMap::get(1, 10), None);
+        assert_eq!(TestDoubleMap::get(2, 10), Some(1));
+    })
+}
+
+#[test]
+fn test_double_map_mutate_and_remove_prefix() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_eq!(
+            TestDoubleMap::mutate(1, 10, |v| {
+                *v += 5;
+                *v
+            }),
+            5
+        );
+        TestDoubleMap::mutate(1, 10, |v| *v += 5);
+        TestDoubleMap::mutate(1, 11, |v| *v = 7);
+        TestDoubleMap::insert(2, 10, 1);
+        assert_eq!(TestDoubleMap::get_or_default(1, 10), 10);
+        assert_eq!(TestDoubleMap::keys(1), vec![10, 11]);
+
+        TestDoubleMap::remove_prefix(1);
+        assert_eq!(TestDoubleMap::count(1), 0);
+        assert_eq!(TestDoubleMap::exists(1, 10), false);
+        assert_eq!(TestDoubleMap::exists(1, 11), false);
+        assert_eq!(TestDoubleMap::get_or_default(1, 10), 0);
+        assert_eq!(TestDoubleMap::count(2), 1);
+        assert_eq!(TestDoubleMap::get(2, 10), Some(1));
+
+        // the count and the slots are stored apart from the values
+        assert_eq!(TestDoubleMap::count_key_for(&1).len(), 16);
+        assert_eq!(TestDoubleMap::slot_key_for(&1, 0).len(), 16);
+        assert_eq!(TestDoubleMap::key_for(&1, &10).len(), 48);
+    })
+}
+
+#[test]
+fn test_double_map_remove_moves_last_slot() {
+    with_externalities(&mut new_test_ext(), || {
+        for k2 in 1..5 {
+            TestDoubleMap::insert(1, k2, k2 as u32 * 10);
+        }
+        TestDoubleMap::remove(1, 2);
+        assert_eq!(TestDoubleMap::keys(1), vec![1, 4, 3]);
+        assert_eq!(TestDoubleMap::count(1), 3);
+
+        // removing the last slot moves nothing
+        TestDoubleMap::remove(1, 3);
+        assert_eq!(TestDoubleMap::keys(1), vec![1, 4]);
+        // a missing entry leaves the slots alone
+        TestDoubleMap::remove(1, 3);
+        assert_eq!(TestDoubleMap::count(1), 2);
+
+        // the moved entry can be removed and inserted again
+        TestDoubleMap::remove(1, 4);
+        TestDoubleMap::insert(1, 4, 41);
+        assert_eq!(
+            TestDoubleMap::iter_prefix(1).collect::<Vec<_>>(),
+            vec![(1, 10), (4, 41)]
+        );
+
+        let storage = TestDoubleMap::genesis_storage(&2, vec![(7, 70), (8, 80)]);
+        for (key, value) in storage {
+            runtime_io::set_storage(&key, &value);
+        }
+        assert_eq!(
+            TestDoubleMap::iter_prefix(2).collect::<Vec<_>>(),
+            vec![(7, 70), (8, 80)]
+        );
+        TestDoubleMap::remove(2, 7);
+        assert_eq!(TestDoubleMap::keys(2), vec![8]);
+    })
+}
+
+struct TestMigration;
+
+impl migration::Migrate for TestMigration {
+    const VERSION_KEY: &'static [u8] = b"CXSupport TestVersion";
+    const STORAGE_VERSION: u32 = 2;
+
+    fn migrate_step(from: u32) {
+        // v0 stores a u32, v1 a u64, v2 a (u64, bool)
+        match from {
+            0 => {
+                let old: u32 = runtime_support::storage::get_or_default(b"CXSupport TestValue");
+                runtime_support::storage::put(b"CXSupport TestValue", &(old as u64));
+            }
+            1 => {
+                let old: u64 = runtime_support::storage::get_or_default(b"CXSupport TestValue");
+                runtime_support::storage::put(b"CXSupport TestValue", &(old, true));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn test_migration_runs_pending_steps_once() {
+    use migration::testing::assert_migrated;
+    use migration::Migrate;
+
+    with_externalities(&mut new_test_ext(), || {
+        runtime_support::storage::put(b"CXSupport TestValue", &7u32);
+        assert_eq!(TestMigration::storage_version(), 0);
+
+        assert_migrated::<TestMigration>();
+        let value: (u64, bool) = runtime_support::storage::get_or_default(b"CXSupport TestValue");
+        assert_eq!(value, (7, true));
+    })
+}
+
+#[test]
+fn test_migration_from_intermediate_version() {
+    use migration::testing::{assert_migrated, set_storage_version};
+    use migration::Migrate;
+
+    with_externalities(&mut new_test_ext(), || {
+        runtime_support::storage::put(b"CXSupport TestValue", &9u64);
+        set_storage_version::<TestMigration>(1);
+
+        assert_migrated::<TestMigration>();
+        let value: (u64, bool) = runtime_support::storage::get_or_default(b"CXSupport TestValue");
+        assert_eq!(value, (9, true));
+
+        // up to date state is left alone
+        assert_eq!(TestMigration::on_runtime_upgrade(), false);
+    })
+}
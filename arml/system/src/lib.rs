@@ -40,11 +40,30 @@ mod tests;
 use rstd::prelude::*;
 use runtime_primitives::traits::OnFinalise;
 use runtime_support::dispatch::Result;
-use runtime_support::StorageValue;
+use runtime_support::{StorageMap, StorageValue};
 
 use system::ensure_inherent;
 
-pub trait Trait: system::Trait {}
+pub trait Trait: system::Trait {
+    /// Run by the block producer inherent, before any signed extrinsic of the block.
+    type OnBlockStart: OnBlockStart;
+}
+
+/// Block start trigger, e.g. to migrate storage before it is dispatched on.
+pub trait OnBlockStart {
+    fn on_block_start();
+}
+
+impl OnBlockStart for () {
+    fn on_block_start() {}
+}
+
+impl<A: OnBlockStart, B: OnBlockStart> OnBlockStart for (A, B) {
+    fn on_block_start() {
+        A::on_block_start();
+        B::on_block_start();
+    }
+}
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
@@ -63,13 +82,47 @@ decl_storage! {
         pub BlockProdocer get(block_producer): Option<T::AccountId>;
         pub DeathAccount get(death_account) config(): T::AccountId;
         pub FeeBuyAccount get(fee_buy_account) config(): T::AccountId;
+
+        /// Blocks authored by each validator in the current era.
+        pub AuthoredBlocks get(authored_blocks): map T::AccountId => u32;
+        /// Validators which authored a block in the current era.
+        pub Authors get(authors): Vec<T::AccountId>;
+        /// Blocks authored by each validator in the previous era.
+        pub LastEraAuthoredBlocks get(last_era_authored_blocks): map T::AccountId => u32;
+        /// Validators which authored a block in the previous era.
+        pub LastEraAuthors get(last_era_authors): Vec<T::AccountId>;
     }
 }
 
 impl<T: Trait> Module<T> {
     pub fn set_block_producer(origin: T::Origin, producer: T::AccountId) -> Result {
         ensure_inherent(origin)?;
+        T::OnBlockStart::on_block_start();
+        if !<AuthoredBlocks<T>>::exists(&producer) {
+            <Authors<T>>::mutate(|authors| authors.push(producer.clone()));
+        }
+        <AuthoredBlocks<T>>::mutate(&producer, |n| *n += 1);
         BlockProdocer::<T>::put(producer);
         Ok(())
     }
+
+    /// Authored blocks of the current and the previous era.
+    pub fn authored_blocks_of(who: &T::AccountId) -> (u32, u32) {
+        (
+            Self::authored_blocks(who),
+            Self::last_era_authored_blocks(who),
+        )
+    }
+
+    /// Start counting the authored blocks of a new era, the current era becomes the previous.
+    pub fn rotate_authored_blocks() {
+        for author in Self::last_era_authors() {
+            <LastEraAuthoredBlocks<T>>::remove(&author);
+        }
+        let authors = <Authors<T>>::take();
+        for author in authors.iter() {
+            <LastEraAuthoredBlocks<T>>::insert(author, <AuthoredBlocks<T>>::take(author));
+        }
+        <LastEraAuthors<T>>::put(authors);
+    }
 }
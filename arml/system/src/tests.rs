@@ -1,4 +1,135 @@
+// Copyright 2018 Akropolis.
+
+use substrate_primitives::{Blake2Hasher, H256};
+
+use runtime_io;
+use runtime_io::with_externalities;
+use runtime_primitives::testing::{Digest, DigestItem, Header};
+use runtime_primitives::traits::BlakeTwo256;
+use runtime_primitives::BuildStorage;
+
+use super::*;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+
+impl system::Trait for Test {
+    type Origin = Origin;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type Digest = Digest;
+    type AccountId = u64;
+    type Header = Header;
+    type Event = ();
+    type Log = DigestItem;
+}
+
+/// Marks in storage that the block start trigger ran.
+pub struct MarkBlockStart;
+
+impl OnBlockStart for MarkBlockStart {
+    fn on_block_start() {
+        runtime_support::storage::put(b"CXSystem TestBlockStart", &true);
+    }
+}
+
+impl Trait for Test {
+    type OnBlockStart = MarkBlockStart;
+}
+
+type CXSystem = Module<Test>;
+
+fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+    let mut t = system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+    t.extend(
+        GenesisConfig::<Test> {
+            death_account: 0,
+            fee_buy_account: 1,
+        }
+        .build_storage()
+        .unwrap(),
+    );
+    runtime_io::TestExternalities::new(t)
+}
+
+#[test]
+fn set_block_producer_should_work() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_eq!(CXSystem::block_producer(), None);
+        assert_eq!(
+            CXSystem::set_block_producer(system::RawOrigin::Inherent.into(), 10),
+            Ok(())
+        );
+        assert_eq!(CXSystem::block_producer(), Some(10));
+
+        // cleared at the end of the block
+        CXSystem::on_finalise(1);
+        assert_eq!(CXSystem::block_producer(), None);
+    });
+}
+
+#[test]
+fn set_block_producer_should_be_inherent() {
+    with_externalities(&mut new_test_ext(), || {
+        assert!(CXSystem::set_block_producer(system::RawOrigin::Signed(10).into(), 10).is_err());
+        assert!(CXSystem::set_block_producer(system::RawOrigin::Root.into(), 10).is_err());
+        assert_eq!(CXSystem::block_producer(), None);
+        assert_eq!(
+            runtime_support::storage::get::<bool>(b"CXSystem TestBlockStart"),
+            None
+        );
+    });
+}
+
+#[test]
+fn set_block_producer_should_trigger_block_start() {
+    with_externalities(&mut new_test_ext(), || {
+        assert_eq!(
+            CXSystem::set_block_producer(system::RawOrigin::Inherent.into(), 10),
+            Ok(())
+        );
+        assert_eq!(
+            runtime_support::storage::get::<bool>(b"CXSystem TestBlockStart"),
+            Some(true)
+        );
+    });
+}
+
 #[test]
-fn it_works() {
-    assert_eq!(2 + 2, 4);
+fn authored_blocks_should_be_counted_per_era() {
+    with_externalities(&mut new_test_ext(), || {
+        let produce = |who| {
+            assert_eq!(
+                CXSystem::set_block_producer(system::RawOrigin::Inherent.into(), who),
+                Ok(())
+            );
+            CXSystem::on_finalise(1);
+        };
+        produce(10);
+        produce(20);
+        produce(10);
+        assert_eq!(CXSystem::authors(), vec![10, 20]);
+        assert_eq!(CXSystem::authored_blocks_of(&10), (2, 0));
+        assert_eq!(CXSystem::authored_blocks_of(&20), (1, 0));
+
+        CXSystem::rotate_authored_blocks();
+        produce(20);
+        assert_eq!(CXSystem::authored_blocks_of(&10), (0, 2));
+        assert_eq!(CXSystem::authored_blocks_of(&20), (1, 1));
+
+        // only the previous era is kept
+        CXSystem::rotate_authored_blocks();
+        assert_eq!(CXSystem::authored_blocks_of(&10), (0, 0));
+        assert_eq!(CXSystem::authored_blocks_of(&20), (0, 1));
+        assert_eq!(CXSystem::authors(), vec![]);
+        assert_eq!(CXSystem::last_era_authors(), vec![20]);
+    });
 }
@@ -1,7 +1,7 @@
 // Copyright 2018 Akropolis.
 
 //! Akro block evaluation and evaluation errors.
-use akro_primitives::{Block, BlockNumber, Hash, Timestamp};
+use akro_primitives::{AccountId, Block, BlockNumber, Hash, Timestamp};
 use akro_runtime::{Block as AkroGenericBlock, CheckedBlock};
 
 use super::MAX_TRANSACTIONS_SIZE;
@@ -34,6 +34,10 @@ error_chain! {
             description("Proposal had wrong number."),
             display("Proposal had wrong number. Expected {:?}, got {:?}", expected, got),
         }
+        WrongProducer(expected: Option<AccountId>, got: Option<AccountId>) {
+            description("Proposal was not produced by the proposer of the round."),
+            display("Proposal was produced by {:?}, expected {:?}", got, expected),
+        }
         ProposalTooLarge(size: usize) {
             description("Proposal exceeded the maximum size."),
             display(
@@ -90,3 +94,67 @@ pub fn evaluate_initial(
 
     Ok(proposal)
 }
+
+/// Check that the proposal was produced by `primary_validator`, the proposer of the current
+/// round, returning the producer.
+pub fn evaluate_producer(
+    proposal: &CheckedBlock,
+    primary_validator: Option<AccountId>,
+) -> Result<AccountId> {
+    match proposal.block_producer() {
+        Some(producer) if Some(producer) == primary_validator => Ok(producer),
+        producer => bail!(ErrorKind::WrongProducer(primary_validator, producer)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use akro_primitives::InherentData;
+    use akro_runtime::{inherent_extrinsics, Header};
+
+    fn proposal(producer: u8) -> Block {
+        let block = AkroGenericBlock {
+            header: Header {
+                parent_hash: [1; 32].into(),
+                number: 1,
+                state_root: Default::default(),
+                extrinsics_root: Default::default(),
+                digest: Default::default(),
+            },
+            extrinsics: inherent_extrinsics(InherentData {
+                timestamp: 100,
+                offline_indices: vec![],
+                block_producer: [producer; 32].into(),
+            }),
+        };
+        Decode::decode(&mut block.encode().as_slice()).unwrap()
+    }
+
+    fn evaluate(producer: u8, primary_validator: Option<AccountId>) -> Result<AccountId> {
+        let proposal = evaluate_initial(&proposal(producer), 100, &[1; 32].into(), 0)?;
+        evaluate_producer(&proposal, primary_validator)
+    }
+
+    fn assert_wrong_producer(result: Result<AccountId>) {
+        match result {
+            Err(Error(ErrorKind::WrongProducer(..), _)) => {}
+            r => panic!("expected a wrong producer error, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn proposal_of_primary_validator_should_be_accepted() {
+        assert_eq!(
+            evaluate(2, Some([2; 32].into())).unwrap(),
+            AccountId::from([2; 32])
+        );
+    }
+
+    #[test]
+    fn proposal_of_other_validator_should_be_rejected() {
+        assert_wrong_producer(evaluate(3, Some([2; 32].into())));
+        // nobody is expected to propose
+        assert_wrong_producer(evaluate(2, None));
+    }
+}
@@ -27,6 +27,7 @@ extern crate log;
 mod error;
 mod evaluation;
 mod offline_tracker;
+mod schedule;
 mod service;
 
 use akro_api::AkroApi;
@@ -48,6 +49,7 @@ type TransactionPool<A> = substrate_transaction_pool::Pool<akro_pool::PoolApi<A>
 
 pub use self::error::{Error, ErrorKind};
 pub use self::offline_tracker::OfflineTracker;
+use self::schedule::RoundSchedule;
 pub use service::Service;
 
 /// Shared offline validator tracker.
@@ -118,6 +120,11 @@ where
 
         let validators = self.client.validators(&id)?;
         self.offline.write().note_new_block(&validators[..]);
+        let stake_weights = validators
+            .iter()
+            .map(|account| self.client.stake_weight(&id, *account))
+            .collect::<Result<Vec<_>, _>>()?;
+        info!("validator stake weight:{:?}", stake_weights);
 
         let local_id = sign_with.public().0.into();
         let (input, output) = self.network.communication_for(
@@ -135,11 +142,11 @@ where
             parent_hash,
             parent_id: id,
             parent_number: parent_header.number,
-            random_seed,
             transaction_pool: self.transaction_pool.clone(),
             offline: self.offline.clone(),
             validators,
             minimum_timestamp: current_timestamp() + self.force_delay,
+            schedule: RoundSchedule::new(&stake_weights, random_seed),
         };
 
         Ok((proposer, input, output))
@@ -154,52 +161,18 @@ pub struct Proposer<C: AkroApi + Send + Sync> {
     parent_hash: Hash,
     parent_id: BlockId,
     parent_number: BlockNumber,
-    random_seed: Hash,
     transaction_pool: Arc<TransactionPool<C>>,
     offline: SharedOfflineTracker,
     validators: Vec<AccountId>,
     minimum_timestamp: u64,
+    schedule: RoundSchedule,
 }
 
 impl<C: AkroApi + Send + Sync> Proposer<C> {
-    fn primary_index(&self, round_number: usize, _len: usize) -> usize {
-        use primitives::uint::U256;
-
-        let validators_len = self.validators.len();
-        let mut stake_weight = self
-            .validators
-            .iter()
-            .map(|account| {
-                let weight = self.client.stake_weight(&self.parent_id, *account).unwrap();
-                if weight == 0 {
-                    1
-                } else {
-                    weight
-                }
-            })
-            .collect::<Vec<_>>();
-        info!(
-            "validator stake weight:{:?}, round_numer:{:?}",
-            stake_weight, round_number
-        );
-        for i in 1..validators_len {
-            stake_weight[i] = stake_weight[i] + stake_weight[i - 1];
-        }
-        let big_len = stake_weight[validators_len - 1] as u128;
-        let big_value = U256::from_big_endian(&self.random_seed.0).low_u64() as u128;
-        let offset = (big_value * big_value) % big_len;
-
-        for i in 0..validators_len {
-            if offset < stake_weight[i] as u128 {
-                return (i + round_number) % validators_len;
-            }
-        }
-        return round_number % validators_len;
-    }
-
     fn primary_validator(&self, round_number: usize) -> Option<AccountId> {
-        let i = self.primary_index(round_number, 0);
-        return Some(self.validators[i]);
+        self.schedule
+            .primary_index(round_number)
+            .map(|i| self.validators[i])
     }
 }
 
@@ -364,20 +337,19 @@ where
             return Box::new(futures::empty());
         }
 
-        // check block_producer
-        match proposal.block_producer() {
-            Some(a) => {
+        // refuse to vote if the block producer is not the proposer of this round.
+        let round_number = self.schedule.current_round();
+        match evaluation::evaluate_producer(&proposal, self.primary_validator(round_number)) {
+            Ok(producer) => {
                 info!(
                     "current block#{:}, producer is [{:}]",
                     self.parent_number + 1,
-                    a
+                    producer
                 );
             }
-            None => {
-                info!(
-                    "current block#{:}, not set producer",
-                    self.parent_number + 1
-                );
+            Err(e) => {
+                debug!(target: "bft", "Invalid proposal in round {}: {:?}", round_number, e);
+                return Box::new(future::ok(false));
             }
         }
 
@@ -484,7 +456,11 @@ where
     }
 
     fn round_proposer(&self, round_number: usize, authorities: &[AuthorityId]) -> AuthorityId {
-        let offset = self.primary_index(round_number, authorities.len());
+        self.schedule.enter_round(round_number);
+        let offset = self
+            .schedule
+            .primary_index(round_number)
+            .expect("BFT runs with at least one authority; qed");
         let proposer = authorities[offset].clone();
         debug!(target: "bft", "proposer for round {} is {}", round_number, proposer);
 
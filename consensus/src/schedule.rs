@@ -0,0 +1,90 @@
+// Copyright 2018 Akropolis.
+
+//! Primary validators of the BFT rounds, picked by stake weight.
+
+use akro_primitives::Hash;
+use primitives::uint::U256;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Primary validator of each round of one BFT instance.
+pub struct RoundSchedule {
+    /// Running sum of the validator stake weights, a validator without stake weighs one.
+    cumulative_weights: Vec<u64>,
+    random_seed: Hash,
+    /// Round of the last proposer asked for, proposals are expected from its primary.
+    round: AtomicUsize,
+}
+
+impl RoundSchedule {
+    pub fn new(stake_weights: &[u64], random_seed: Hash) -> Self {
+        let cumulative_weights = stake_weights
+            .iter()
+            .scan(0u64, |sum, &weight| {
+                *sum += weight.max(1);
+                Some(*sum)
+            })
+            .collect();
+        RoundSchedule {
+            cumulative_weights,
+            random_seed,
+            round: AtomicUsize::new(0),
+        }
+    }
+
+    /// Index of the primary validator of `round_number`, none without validators.
+    pub fn primary_index(&self, round_number: usize) -> Option<usize> {
+        let validators_len = self.cumulative_weights.len();
+        let big_len = *self.cumulative_weights.last()? as u128;
+        let big_value = U256::from_big_endian(&self.random_seed.0).low_u64() as u128;
+        let offset = (big_value * big_value) % big_len;
+
+        let first = self
+            .cumulative_weights
+            .iter()
+            .position(|&weight| offset < weight as u128)
+            .unwrap_or(0);
+        Some((first + round_number) % validators_len)
+    }
+
+    /// Note that `round_number` started, BFT asks for the proposer of every round it enters.
+    pub fn enter_round(&self, round_number: usize) {
+        self.round.store(round_number, Ordering::Release);
+    }
+
+    pub fn current_round(&self) -> usize {
+        self.round.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primary_rotates_from_the_seeded_validator() {
+        // a zero seed picks the first validator
+        let schedule = RoundSchedule::new(&[0, 5, 1], Hash::default());
+        assert_eq!(schedule.primary_index(0), Some(0));
+        assert_eq!(schedule.primary_index(2), Some(2));
+        assert_eq!(schedule.primary_index(4), Some(1));
+
+        assert_eq!(
+            RoundSchedule::new(&[], Hash::default()).primary_index(0),
+            None
+        );
+    }
+
+    #[test]
+    fn round_is_known_without_earlier_round_ends() {
+        let schedule = RoundSchedule::new(&[1, 1, 1], Hash::default());
+        // joined in round 4, no round before it ended here
+        schedule.enter_round(4);
+        assert_eq!(schedule.current_round(), 4);
+        assert_eq!(schedule.primary_index(schedule.current_round()), Some(1));
+
+        // a jump over several rounds
+        schedule.enter_round(9);
+        assert_eq!(schedule.primary_index(schedule.current_round()), Some(0));
+    }
+}
@@ -0,0 +1,29 @@
+use akro_api;
+use akro_primitives::Hash;
+use std::io;
+
+error_chain! {
+    links {
+        Api(akro_api::Error, akro_api::ErrorKind);
+    }
+    foreign_links {
+        Io(io::Error) #[doc = "Database error"];
+    }
+    errors {
+        /// A block of the chain to index is not known to the client.
+        UnknownBlock(hash: Hash) {
+            description("Unknown block"),
+            display("Unknown block {:?}", hash),
+        }
+        /// Stored data, or data of the chain, which can not be decoded.
+        Undecodable(what: &'static str) {
+            description("Undecodable data"),
+            display("Undecodable {}", what),
+        }
+        /// Stored data which should be there but is not, the database is inconsistent.
+        Missing(what: &'static str) {
+            description("Missing data"),
+            display("Missing {}", what),
+        }
+    }
+}
@@ -0,0 +1,334 @@
+// Copyright 2018 Akropolis.
+
+//! Storage of the indexed account history.
+
+use akro_primitives::{AccountId, Balance, BlockNumber, Hash};
+use codec::{Decode, Encode};
+use kvdb::{DBTransaction, KeyValueDB};
+use kvdb_rocksdb::{Database, DatabaseConfig};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use error::{ErrorKind, Result};
+
+/// Entries of the account history returned per page.
+pub const PAGE_SIZE: usize = 50;
+
+pub const NUM_COLUMNS: u32 = 4;
+
+mod columns {
+    /// The last indexed block.
+    pub const META: Option<u32> = Some(0);
+    /// Hash of each indexed block and the number of entries it has for each account, by block
+    /// number.
+    pub const BLOCKS: Option<u32> = Some(1);
+    /// Entries of an account, by account and sequence number.
+    pub const HISTORY: Option<u32> = Some(2);
+    /// Number of entries of an account, by account.
+    pub const COUNTS: Option<u32> = Some(3);
+}
+
+const TIP_KEY: &[u8] = b"tip";
+
+/// How an extrinsic concerns an account.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    /// The account signed the extrinsic.
+    Sent,
+    /// The extrinsic transferred balance from the account to another one.
+    TransferOut(AccountId, Balance),
+    /// The extrinsic transferred balance to the account from another one.
+    TransferIn(AccountId, Balance),
+}
+
+/// An extrinsic which concerns an account.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub block_number: BlockNumber,
+    pub block_hash: Hash,
+    pub extrinsic_index: u32,
+    pub extrinsic_hash: Hash,
+    pub kind: EntryKind,
+    /// Whether the extrinsic was applied successfully.
+    pub success: bool,
+}
+
+fn number_key(number: BlockNumber) -> [u8; 8] {
+    let mut key = [0u8; 8];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = (number >> (8 * (7 - i))) as u8;
+    }
+    key
+}
+
+/// Account first, then the sequence number of the entry in big endian.
+fn history_key(who: &AccountId, seq: u64) -> Vec<u8> {
+    let mut key = who.as_bytes().to_vec();
+    key.extend_from_slice(&number_key(seq));
+    key
+}
+
+fn decode<T: Decode>(data: &[u8], what: &'static str) -> Result<T> {
+    Decode::decode(&mut &data[..]).ok_or_else(|| ErrorKind::Undecodable(what).into())
+}
+
+/// Per-account history of the indexed blocks.
+///
+/// Blocks are indexed in order. Reverting drops the history of every block from the given
+/// number on, so a reorganisation is applied by reverting to the common ancestor and indexing
+/// the new best chain from there.
+///
+/// The entries of an account are numbered in the order they are indexed, a page is read by
+/// seeking back from the number of entries of the account.
+pub struct AccountHistory {
+    db: Arc<KeyValueDB>,
+}
+
+impl AccountHistory {
+    /// Open the history database at `path`, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let config = DatabaseConfig::with_columns(Some(NUM_COLUMNS));
+        let path = path.to_string_lossy();
+        let db = Database::open(&config, &path)?;
+        Ok(Self::new(Arc::new(db)))
+    }
+
+    pub fn new(db: Arc<KeyValueDB>) -> Self {
+        AccountHistory { db }
+    }
+
+    /// Number and hash of the last indexed block.
+    pub fn tip(&self) -> Result<Option<(BlockNumber, Hash)>> {
+        match self.db.get(columns::META, TIP_KEY)? {
+            Some(data) => Ok(Some(decode(&data, "tip")?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Hash of the block indexed at `number`.
+    pub fn indexed_hash(&self, number: BlockNumber) -> Result<Option<Hash>> {
+        Ok(self.indexed_block(number)?.map(|(hash, _)| hash))
+    }
+
+    /// Hash of the block indexed at `number` and the number of entries it has for each account.
+    fn indexed_block(&self, number: BlockNumber) -> Result<Option<(Hash, Vec<(AccountId, u64)>)>> {
+        match self.db.get(columns::BLOCKS, &number_key(number))? {
+            Some(data) => Ok(Some(decode(&data, "indexed block")?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Number of entries of `who`.
+    fn count(&self, who: &AccountId) -> Result<u64> {
+        match self.db.get(columns::COUNTS, who.as_bytes())? {
+            Some(data) => decode(&data, "entry count"),
+            None => Ok(0),
+        }
+    }
+
+    /// Store the entries of a block on top of the indexed ones.
+    pub fn index_block(
+        &self,
+        number: BlockNumber,
+        hash: Hash,
+        entries: Vec<(AccountId, HistoryEntry)>,
+    ) -> Result<()> {
+        // entries of each account before and in this block
+        let mut counts: BTreeMap<AccountId, (u64, u64)> = BTreeMap::new();
+        let mut tx = DBTransaction::new();
+        for (who, entry) in entries {
+            if !counts.contains_key(&who) {
+                counts.insert(who, (self.count(&who)?, 0));
+            }
+            let (before, added) = counts.get_mut(&who).expect("inserted above; qed");
+            tx.put(
+                columns::HISTORY,
+                &history_key(&who, *before + *added),
+                &entry.encode(),
+            );
+            *added += 1;
+        }
+
+        for (who, (before, added)) in counts.iter() {
+            tx.put(columns::COUNTS, who.as_bytes(), &(before + added).encode());
+        }
+        let accounts: Vec<(AccountId, u64)> = counts
+            .into_iter()
+            .map(|(who, (_, added))| (who, added))
+            .collect();
+        tx.put(
+            columns::BLOCKS,
+            &number_key(number),
+            &(hash, accounts).encode(),
+        );
+        tx.put(columns::META, TIP_KEY, &(number, hash).encode());
+        self.db.write(tx)?;
+        Ok(())
+    }
+
+    /// Drop the history of every block from `number` on.
+    pub fn revert_from(&self, number: BlockNumber) -> Result<()> {
+        let tip = match self.tip()? {
+            Some((tip, _)) if tip >= number => tip,
+            _ => return Ok(()),
+        };
+
+        // the reverted blocks hold the latest entries of each account
+        let mut reverted: BTreeMap<AccountId, u64> = BTreeMap::new();
+        let mut tx = DBTransaction::new();
+        for n in number..=tip {
+            if let Some((_, accounts)) = self.indexed_block(n)? {
+                for (who, added) in accounts {
+                    *reverted.entry(who).or_insert(0) += added;
+                }
+                tx.delete(columns::BLOCKS, &number_key(n));
+            }
+        }
+        for (who, reverted) in reverted {
+            let count = self.count(&who)?;
+            let kept = count.saturating_sub(reverted);
+            for seq in kept..count {
+                tx.delete(columns::HISTORY, &history_key(&who, seq));
+            }
+            if kept == 0 {
+                tx.delete(columns::COUNTS, who.as_bytes());
+            } else {
+                tx.put(columns::COUNTS, who.as_bytes(), &kept.encode());
+            }
+        }
+        let parent = match number.checked_sub(1) {
+            Some(parent) => self.indexed_hash(parent)?.map(|hash| (parent, hash)),
+            None => None,
+        };
+        match parent {
+            Some(parent) => tx.put(columns::META, TIP_KEY, &parent.encode()),
+            None => tx.delete(columns::META, TIP_KEY),
+        }
+        self.db.write(tx)?;
+        Ok(())
+    }
+
+    /// A page of the history of `who`, the latest entries first.
+    pub fn history(&self, who: &AccountId, page: u32) -> Result<Vec<HistoryEntry>> {
+        let end = self
+            .count(who)?
+            .saturating_sub(page as u64 * PAGE_SIZE as u64);
+        let start = end.saturating_sub(PAGE_SIZE as u64);
+        (start..end)
+            .rev()
+            .map(
+                |seq| match self.db.get(columns::HISTORY, &history_key(who, seq))? {
+                    Some(data) => decode(&data, "history"),
+                    None => Err(ErrorKind::Missing("history entry").into()),
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvdb_memorydb;
+
+    fn history() -> AccountHistory {
+        AccountHistory::new(Arc::new(kvdb_memorydb::create(NUM_COLUMNS)))
+    }
+
+    fn entry(number: BlockNumber, extrinsic_index: u32, kind: EntryKind) -> HistoryEntry {
+        HistoryEntry {
+            block_number: number,
+            block_hash: [number as u8; 32].into(),
+            extrinsic_index,
+            extrinsic_hash: [extrinsic_index as u8; 32].into(),
+            kind,
+            success: true,
+        }
+    }
+
+    fn index_transfer(history: &AccountHistory, number: BlockNumber, from: u8, to: u8) {
+        let (from, to): (AccountId, AccountId) = ([from; 32].into(), [to; 32].into());
+        history
+            .index_block(
+                number,
+                [number as u8; 32].into(),
+                vec![
+                    (from, entry(number, 1, EntryKind::Sent)),
+                    (from, entry(number, 1, EntryKind::TransferOut(to, 10))),
+                    (to, entry(number, 1, EntryKind::TransferIn(from, 10))),
+                ],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn history_should_be_indexed_per_account() {
+        let history = history();
+        index_transfer(&history, 0, 1, 2);
+        index_transfer(&history, 1, 2, 3);
+
+        assert_eq!(history.tip().unwrap(), Some((1, [1; 32].into())));
+        assert_eq!(history.indexed_hash(0).unwrap(), Some([0; 32].into()));
+
+        // latest first
+        let of_2 = history.history(&[2; 32].into(), 0).unwrap();
+        assert_eq!(
+            of_2,
+            vec![
+                entry(1, 1, EntryKind::TransferOut([3; 32].into(), 10)),
+                entry(1, 1, EntryKind::Sent),
+                entry(0, 1, EntryKind::TransferIn([1; 32].into(), 10)),
+            ]
+        );
+        assert_eq!(history.history(&[3; 32].into(), 0).unwrap().len(), 1);
+        assert!(history.history(&[4; 32].into(), 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_should_be_paginated() {
+        let history = history();
+        for number in 0..PAGE_SIZE as u64 + 5 {
+            history
+                .index_block(
+                    number,
+                    [number as u8; 32].into(),
+                    vec![([1; 32].into(), entry(number, 1, EntryKind::Sent))],
+                )
+                .unwrap();
+        }
+
+        let first = history.history(&[1; 32].into(), 0).unwrap();
+        assert_eq!(first.len(), PAGE_SIZE);
+        assert_eq!(first[0].block_number, PAGE_SIZE as u64 + 4);
+        let second = history.history(&[1; 32].into(), 1).unwrap();
+        assert_eq!(second.len(), 5);
+        assert_eq!(second[4].block_number, 0);
+        assert!(history.history(&[1; 32].into(), 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn revert_should_drop_later_blocks() {
+        let history = history();
+        index_transfer(&history, 0, 1, 2);
+        index_transfer(&history, 1, 1, 3);
+        index_transfer(&history, 2, 1, 4);
+
+        history.revert_from(1).unwrap();
+        assert_eq!(history.tip().unwrap(), Some((0, [0; 32].into())));
+        assert_eq!(history.indexed_hash(1).unwrap(), None);
+        assert_eq!(history.history(&[1; 32].into(), 0).unwrap().len(), 2);
+        assert!(history.history(&[3; 32].into(), 0).unwrap().is_empty());
+        assert!(history.history(&[4; 32].into(), 0).unwrap().is_empty());
+
+        // reverting past the tip changes nothing
+        history.revert_from(5).unwrap();
+        assert_eq!(history.tip().unwrap(), Some((0, [0; 32].into())));
+
+        history.revert_from(0).unwrap();
+        assert_eq!(history.tip().unwrap(), None);
+        assert!(history.history(&[1; 32].into(), 0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+// Copyright 2018 Akropolis.
+
+//! Local index of the extrinsics each account sent or received.
+//!
+//! The indexer follows the best chain of the client and keeps the history in a database of
+//! its own. Blocks are decoded with the runtime types, so it needs the state of the blocks it
+//! indexes: catching up over blocks whose state is pruned fails.
+
+extern crate akro_api;
+extern crate akro_primitives;
+extern crate akro_runtime;
+extern crate kvdb;
+extern crate kvdb_rocksdb;
+extern crate parity_codec as codec;
+extern crate serde;
+extern crate sr_primitives as runtime_primitives;
+extern crate srml_balances as balances;
+extern crate srml_system as system;
+extern crate substrate_client;
+extern crate substrate_primitives;
+
+#[cfg(test)]
+extern crate kvdb_memorydb;
+#[macro_use]
+extern crate parity_codec_derive;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate error_chain;
+#[macro_use]
+extern crate log;
+
+mod error;
+mod history;
+
+pub use error::{Error, ErrorKind, Result};
+pub use history::{AccountHistory, EntryKind, HistoryEntry, PAGE_SIZE};
+
+use akro_api::{AkroApi, TClient};
+use akro_primitives::{AccountId, BlockId, BlockNumber, Hash};
+use akro_runtime::{Event, UncheckedExtrinsic};
+use codec::{Decode, Encode};
+use runtime_primitives::traits::{BlakeTwo256, Hash as HashT};
+use std::collections::HashMap;
+use std::sync::Arc;
+use substrate_primitives::storage::StorageKey;
+use substrate_primitives::twox_128;
+use system::{EventRecord, Phase};
+
+/// The blocks an indexer reads.
+pub trait Chain {
+    /// Number and parent hash of the block `hash`.
+    fn header(&self, hash: Hash) -> Result<Option<(BlockNumber, Hash)>>;
+
+    /// History entries of the accounts concerned by the extrinsics of a block.
+    fn block_entries(
+        &self,
+        number: BlockNumber,
+        hash: Hash,
+    ) -> Result<Vec<(AccountId, HistoryEntry)>>;
+}
+
+/// Indexes the best chain of a client into an account history.
+pub struct Indexer<C: Chain = TClient> {
+    client: Arc<C>,
+    history: Arc<AccountHistory>,
+}
+
+impl<C: Chain> Indexer<C> {
+    pub fn new(client: Arc<C>, history: Arc<AccountHistory>) -> Self {
+        Indexer { client, history }
+    }
+
+    /// Index the chain up to the new best block `best`.
+    ///
+    /// Indexed blocks which are not part of that chain any more are reverted first.
+    pub fn sync(&self, best: Hash) -> Result<()> {
+        // blocks of the best chain which are not indexed yet, latest first
+        let mut route = Vec::new();
+        let mut hash = best;
+        loop {
+            let (number, parent) = self
+                .client
+                .header(hash)?
+                .ok_or(ErrorKind::UnknownBlock(hash))?;
+            if self.history.indexed_hash(number)? == Some(hash) {
+                break;
+            }
+            route.push((number, hash));
+            if number == 0 {
+                break;
+            }
+            hash = parent;
+        }
+
+        if let Some(&(from, _)) = route.last() {
+            if self.history.tip()?.map_or(false, |(tip, _)| tip >= from) {
+                info!("Reindexing accounts from block #{}", from);
+            }
+            self.history.revert_from(from)?;
+        }
+        for (number, hash) in route.into_iter().rev() {
+            let entries = self.block_entries(number, hash)?;
+            self.history.index_block(number, hash, entries)?;
+        }
+        Ok(())
+    }
+}
+
+/// System event records of the block `at`.
+fn event_records(client: &TClient, at: &BlockId) -> Result<Vec<EventRecord<Event>>> {
+    let key = StorageKey(twox_128(b"System Events").to_vec());
+    match client.storage(at, &key)? {
+        Some(data) => Ok(Decode::decode(&mut &data.0[..]).ok_or(ErrorKind::Undecodable("events"))?),
+        None => Ok(Vec::new()),
+    }
+}
+
+impl Chain for TClient {
+    fn header(&self, hash: Hash) -> Result<Option<(BlockNumber, Hash)>> {
+        // the inherent method of the client
+        let header = substrate_client::Client::header(self, &BlockId::hash(hash))?;
+        Ok(header.map(|header| (header.number, header.parent_hash)))
+    }
+
+    /// History entries of the accounts concerned by the extrinsics of a block.
+    fn block_entries(
+        &self,
+        number: BlockNumber,
+        block_hash: Hash,
+    ) -> Result<Vec<(AccountId, HistoryEntry)>> {
+        let at = BlockId::hash(block_hash);
+        let extrinsics = self.body(&at)?.unwrap_or_default();
+        let records = event_records(self, &at)?;
+
+        let mut success = HashMap::new();
+        let mut transfers = Vec::new();
+        for record in records {
+            match (record.phase, record.event) {
+                (Phase::ApplyExtrinsic(i), Event::system(system::Event::ExtrinsicSuccess)) => {
+                    success.insert(i, true);
+                }
+                (Phase::ApplyExtrinsic(i), Event::system(system::Event::ExtrinsicFailed)) => {
+                    success.insert(i, false);
+                }
+                (
+                    Phase::ApplyExtrinsic(i),
+                    Event::balances(balances::RawEvent::Transfer(from, to, value, _)),
+                ) => transfers.push((i, from, to, value)),
+                _ => {}
+            }
+        }
+
+        // the opaque extrinsics encode to the same bytes as the runtime ones
+        let extrinsics = extrinsics
+            .iter()
+            .map(|xt| {
+                let encoded = xt.encode();
+                let xt: UncheckedExtrinsic =
+                    Decode::decode(&mut &encoded[..]).ok_or(ErrorKind::Undecodable("extrinsic"))?;
+                Ok((BlakeTwo256::hash(&encoded), xt))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let entry = |i: u32, kind: EntryKind| HistoryEntry {
+            block_number: number,
+            block_hash,
+            extrinsic_index: i,
+            extrinsic_hash: extrinsics[i as usize].0,
+            kind,
+            success: success.get(&i).cloned().unwrap_or(false),
+        };
+
+        let mut entries = Vec::new();
+        for (i, &(_, ref xt)) in extrinsics.iter().enumerate() {
+            if let Some((ref address, _, _, _)) = xt.signature {
+                if let Some(sender) = self.lookup(&at, address.clone())? {
+                    entries.push((sender, entry(i as u32, EntryKind::Sent)));
+                }
+            }
+        }
+        for (i, from, to, value) in transfers {
+            if i as usize >= extrinsics.len() {
+                continue;
+            }
+            entries.push((from, entry(i, EntryKind::TransferOut(to, value))));
+            entries.push((to, entry(i, EntryKind::TransferIn(from, value))));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kvdb_memorydb;
+
+    /// Blocks by hash: number, parent hash and the accounts the block has an entry for.
+    #[derive(Default)]
+    struct TestChain(HashMap<Hash, (BlockNumber, Hash, Vec<AccountId>)>);
+
+    impl TestChain {
+        fn push(&mut self, number: BlockNumber, fork: u8, parent_fork: u8, accounts: &[u8]) {
+            let parent = if number == 0 {
+                Hash::default()
+            } else {
+                block_hash(number - 1, parent_fork)
+            };
+            let accounts = accounts.iter().map(|&who| [who; 32].into()).collect();
+            self.0
+                .insert(block_hash(number, fork), (number, parent, accounts));
+        }
+    }
+
+    impl Chain for TestChain {
+        fn header(&self, hash: Hash) -> Result<Option<(BlockNumber, Hash)>> {
+            Ok(self
+                .0
+                .get(&hash)
+                .map(|&(number, parent, _)| (number, parent)))
+        }
+
+        fn block_entries(
+            &self,
+            number: BlockNumber,
+            hash: Hash,
+        ) -> Result<Vec<(AccountId, HistoryEntry)>> {
+            let accounts = self.0[&hash].2.clone();
+            Ok(accounts
+                .into_iter()
+                .map(|who| (who, entry(number, hash)))
+                .collect())
+        }
+    }
+
+    fn block_hash(number: BlockNumber, fork: u8) -> Hash {
+        let mut hash = [number as u8; 32];
+        hash[0] = fork;
+        hash.into()
+    }
+
+    fn entry(number: BlockNumber, hash: Hash) -> HistoryEntry {
+        HistoryEntry {
+            block_number: number,
+            block_hash: hash,
+            extrinsic_index: 1,
+            extrinsic_hash: hash,
+            kind: EntryKind::Sent,
+            success: true,
+        }
+    }
+
+    fn indexer(chain: TestChain) -> (Indexer<TestChain>, Arc<AccountHistory>) {
+        let history = Arc::new(AccountHistory::new(Arc::new(kvdb_memorydb::create(
+            history::NUM_COLUMNS,
+        ))));
+        (Indexer::new(Arc::new(chain), history.clone()), history)
+    }
+
+    fn blocks_of(history: &AccountHistory, who: u8) -> Vec<Hash> {
+        history
+            .history(&[who; 32].into(), 0)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.block_hash)
+            .collect()
+    }
+
+    #[test]
+    fn sync_should_catch_up_with_the_best_block() {
+        let mut chain = TestChain::default();
+        for number in 0..4 {
+            chain.push(number, 1, 1, &[1]);
+        }
+        let (indexer, history) = indexer(chain);
+
+        indexer.sync(block_hash(1, 1)).unwrap();
+        assert_eq!(history.tip().unwrap(), Some((1, block_hash(1, 1))));
+        assert_eq!(
+            blocks_of(&history, 1),
+            vec![block_hash(1, 1), block_hash(0, 1)]
+        );
+
+        indexer.sync(block_hash(3, 1)).unwrap();
+        assert_eq!(history.tip().unwrap(), Some((3, block_hash(3, 1))));
+        assert_eq!(blocks_of(&history, 1).len(), 4);
+
+        // syncing to an indexed block changes nothing
+        indexer.sync(block_hash(3, 1)).unwrap();
+        assert_eq!(blocks_of(&history, 1).len(), 4);
+    }
+
+    #[test]
+    fn sync_should_reindex_on_reorg() {
+        let mut chain = TestChain::default();
+        chain.push(0, 1, 1, &[1]);
+        chain.push(1, 1, 1, &[1]);
+        chain.push(2, 1, 1, &[1, 2]);
+        chain.push(3, 1, 1, &[1, 2]);
+        // fork of the block #1
+        chain.push(2, 2, 1, &[1, 3]);
+        let (indexer, history) = indexer(chain);
+
+        indexer.sync(block_hash(3, 1)).unwrap();
+        assert_eq!(blocks_of(&history, 2).len(), 2);
+
+        indexer.sync(block_hash(2, 2)).unwrap();
+        assert_eq!(history.tip().unwrap(), Some((2, block_hash(2, 2))));
+        assert_eq!(history.indexed_hash(1).unwrap(), Some(block_hash(1, 1)));
+        assert_eq!(history.indexed_hash(3).unwrap(), None);
+        assert!(blocks_of(&history, 2).is_empty());
+        assert_eq!(blocks_of(&history, 3), vec![block_hash(2, 2)]);
+        assert_eq!(
+            blocks_of(&history, 1),
+            vec![block_hash(2, 2), block_hash(1, 1), block_hash(0, 1)]
+        );
+
+        // and back to the longer fork
+        indexer.sync(block_hash(3, 1)).unwrap();
+        assert_eq!(history.tip().unwrap(), Some((3, block_hash(3, 1))));
+        assert!(blocks_of(&history, 3).is_empty());
+        assert_eq!(
+            blocks_of(&history, 2),
+            vec![block_hash(3, 1), block_hash(2, 1)]
+        );
+        assert_eq!(blocks_of(&history, 1).len(), 4);
+    }
+
+    #[test]
+    fn sync_should_fail_on_unknown_block() {
+        let mut chain = TestChain::default();
+        chain.push(0, 1, 1, &[1]);
+        let (indexer, history) = indexer(chain);
+
+        let err = indexer.sync(block_hash(1, 1)).err().unwrap();
+        match *err.kind() {
+            ErrorKind::UnknownBlock(hash) => assert_eq!(hash, block_hash(1, 1)),
+            _ => panic!("unexpected error {:?}", err),
+        }
+        assert_eq!(history.tip().unwrap(), None);
+    }
+}
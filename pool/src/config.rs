@@ -0,0 +1,56 @@
+// Copyright 2018 Akropolis.
+
+//! Limits and policies of the transaction pool.
+
+use extrinsic_pool::Options;
+use std::time::Duration;
+
+/// Percentage by which a transaction must out-pay a pooled one of the same sender and index to
+/// replace it, unless configured otherwise.
+pub const DEFAULT_FEE_BUMP: u32 = 10;
+
+/// Configuration of the transaction pool.
+///
+/// When a limit is reached, the pooled transaction paying the lowest fee is evicted to make room,
+/// and a new transaction paying no more than it is rejected.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Most transactions in the pool.
+    pub max_count: usize,
+    /// Most memory the encoded transactions may use, in bytes.
+    pub max_mem_usage: usize,
+    /// Most transactions of a single sender.
+    pub max_per_sender: usize,
+    /// Most indices a transaction may be ahead of the next index of its sender.
+    pub max_future: u64,
+    /// Percentage by which a transaction must out-pay a pooled one of the same sender and index
+    /// to replace it.
+    pub fee_bump: u32,
+    /// How long a transaction rejected as invalid is refused without being verified again.
+    pub ban_time: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        let options = Options::default();
+        PoolConfig {
+            max_count: options.max_count,
+            max_mem_usage: options.max_mem_usage,
+            max_per_sender: options.max_per_sender,
+            max_future: 64,
+            fee_bump: DEFAULT_FEE_BUMP,
+            ban_time: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Limits of the underlying pool.
+    pub fn options(&self) -> Options {
+        Options {
+            max_count: self.max_count,
+            max_mem_usage: self.max_mem_usage,
+            max_per_sender: self.max_per_sender,
+        }
+    }
+}
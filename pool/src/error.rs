@@ -1,13 +1,17 @@
 use akro_api;
-use akro_primitives::Hash;
+use akro_primitives::{Hash, Index};
 use akro_runtime::{Address, UncheckedExtrinsic};
 use extrinsic_pool;
+use std::io;
 
 error_chain! {
     links {
         Pool(extrinsic_pool::Error, extrinsic_pool::ErrorKind);
         Api(akro_api::Error, akro_api::ErrorKind);
     }
+    foreign_links {
+        Io(io::Error) #[doc = "Journal file error"];
+    }
     errors {
         /// Unexpected extrinsic format submitted
         InvalidExtrinsicFormat {
@@ -44,6 +48,36 @@ error_chain! {
             description("Extrinsic too large"),
             display("Extrinsic is too large ({} > {})", got, max),
         }
+        /// Extrinsic index too far ahead of the next index of its sender
+        TooFarInFuture(index: Index, next: Index) {
+            description("Extrinsic index too far in the future"),
+            display("Extrinsic index {} is too far ahead of the next index {}", index, next),
+        }
+        /// Pool journal which can not be decoded
+        InvalidJournal {
+            description("Invalid pool journal"),
+            display("Pool journal can not be decoded"),
+        }
+        /// Extrinsic recently rejected as invalid
+        TemporarilyBanned(hash: Hash) {
+            description("Extrinsic is temporarily banned"),
+            display("Extrinsic {:?} was recently rejected as invalid", hash),
+        }
+        /// Extrinsic signed for the death account
+        DeathAccount {
+            description("Death account can not send transactions"),
+            display("Death account can not send transactions"),
+        }
+        /// Watching an extrinsic already in the pool
+        WatchUnsupported(hash: Hash) {
+            description("Watching a pooled transaction is not supported."),
+            display("Transaction {:?} can only be watched when it is submitted.", hash),
+        }
+        /// Extrinsic which is not in the pool
+        UnknownTransaction(hash: Hash) {
+            description("Transaction is not in the pool."),
+            display("Transaction {:?} is not in the pool.", hash),
+        }
     }
 }
 
@@ -0,0 +1,181 @@
+// Copyright 2018 Akropolis.
+
+//! Journal of the ready transactions, to keep them in the pool across restarts.
+
+use akro_api::AkroApi;
+use codec::{Decode, Encode};
+use error::{Error, ErrorKind, Result};
+use extrinsic_pool::{ChainApi, ExtrinsicFor, Pool};
+use pool::TransactionPool;
+use runtime_primitives::generic::BlockId;
+use std::fs;
+use std::path::PathBuf;
+
+/// File holding the encoded ready transactions of a pool.
+///
+/// The journal is rewritten as a whole on each save. Loading submits the transactions again, so
+/// they are verified like new ones, and those included or expired since they were saved are
+/// culled.
+///
+/// Only the ready transactions are journaled: future ones, waiting for an earlier transaction
+/// of their sender, are lost on a restart and have to be submitted again.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Journal { path: path.into() }
+    }
+
+    /// Write the ready transactions of `pool`, returning how many were written.
+    pub fn save<A>(&self, pool: &TransactionPool<A>) -> Result<usize>
+    where
+        A: AkroApi + Send + Sync,
+    {
+        match pool.best_block_id() {
+            Some(at) => self.save_at(&pool.inner(), &at),
+            None => Ok(0),
+        }
+    }
+
+    /// Submit the journaled transactions to `pool`, returning how many are pooled again.
+    pub fn load<A>(&self, pool: &TransactionPool<A>) -> Result<usize>
+    where
+        A: AkroApi + Send + Sync,
+    {
+        match pool.best_block_id() {
+            Some(at) => self.load_at(&pool.inner(), &at),
+            None => Ok(0),
+        }
+    }
+
+    /// Write the transactions of `pool` which are ready on top of the block `at`.
+    fn save_at<B>(&self, pool: &Pool<B>, at: &BlockId<B::Block>) -> Result<usize>
+    where
+        B: ChainApi<Error = Error>,
+    {
+        let xts: Vec<ExtrinsicFor<B>> =
+            pool.cull_and_get_pending(at, |pending| pending.map(|t| t.original.clone()).collect())?;
+
+        // a crash while writing leaves the previous journal in place
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, xts.encode())?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(xts.len())
+    }
+
+    /// Submit the journaled transactions to `pool` on top of the block `at`.
+    fn load_at<B>(&self, pool: &Pool<B>, at: &BlockId<B::Block>) -> Result<usize>
+    where
+        B: ChainApi<Error = Error>,
+    {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let data = fs::read(&self.path)?;
+        let xts: Vec<ExtrinsicFor<B>> =
+            Decode::decode(&mut &data[..]).ok_or(ErrorKind::InvalidJournal)?;
+
+        // cull the pool first, so that only journaled transactions are culled after the import
+        pool.cull(at)?;
+        let total = xts.len();
+        let mut submitted = 0;
+        for xt in xts {
+            match pool.submit_one(at, xt) {
+                Ok(_) => submitted += 1,
+                Err(e) => {
+                    debug!(target: "transaction-pool", "Dropping journaled transaction: {}", e)
+                }
+            }
+        }
+        // transactions included or expired since the journal was saved are stale now
+        let stale = pool.cull(at)?;
+        let pooled = submitted.saturating_sub(stale);
+        info!(
+            "Restored {} of {} journaled transactions, {} were included or expired meanwhile",
+            pooled, total, stale
+        );
+        Ok(pooled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{extrinsic, pending_fees, TestApi};
+    use std::env;
+    use std::process;
+
+    /// Journal in a file of its own, removed when dropped.
+    struct TestJournal(Journal);
+
+    impl TestJournal {
+        fn new(name: &str) -> Self {
+            let file = format!("akro-pool-journal-{}-{}", name, process::id());
+            TestJournal(Journal::new(env::temp_dir().join(file)))
+        }
+    }
+
+    impl Drop for TestJournal {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0.path);
+        }
+    }
+
+    /// Pool with ready transactions of the senders 1 and 2, and a future one of the sender 3.
+    fn saved_pool() -> Pool<TestApi> {
+        let pool = Pool::new(Default::default(), TestApi::default());
+        let at = BlockId::number(0);
+        for xt in vec![
+            extrinsic(1, 0, 10),
+            extrinsic(1, 1, 10),
+            extrinsic(2, 0, 20),
+            extrinsic(3, 5, 30),
+        ] {
+            pool.submit_one(&at, xt).unwrap();
+        }
+        pool
+    }
+
+    #[test]
+    fn ready_transactions_should_be_restored() {
+        let journal = TestJournal::new("restore");
+        let at = BlockId::number(0);
+        let pool = saved_pool();
+        assert_eq!(journal.0.save_at(&pool, &at).unwrap(), 3);
+
+        let restored = Pool::new(Default::default(), TestApi::default());
+        assert_eq!(journal.0.load_at(&restored, &at).unwrap(), 3);
+        assert_eq!(pending_fees(&restored), pending_fees(&pool));
+        // the future transaction is not journaled
+        assert_eq!(restored.light_status().transaction_count, 3);
+    }
+
+    #[test]
+    fn included_transactions_should_not_be_restored() {
+        let journal = TestJournal::new("included");
+        let at = BlockId::number(0);
+        journal.0.save_at(&saved_pool(), &at).unwrap();
+
+        let api = TestApi::default();
+        let chain = api.chain.clone();
+        let restored = Pool::new(Default::default(), api);
+        // a stale transaction already in the pool is not counted as a journaled one
+        restored.submit_one(&at, extrinsic(4, 0, 10)).unwrap();
+        chain.indices.lock().insert([4; 32].into(), 1);
+        // the first transaction of the sender 1 was included since the journal was saved
+        chain.indices.lock().insert([1; 32].into(), 1);
+
+        assert_eq!(journal.0.load_at(&restored, &at).unwrap(), 2);
+        assert_eq!(pending_fees(&restored), vec![(2, 0, 20), (1, 1, 10)]);
+    }
+
+    #[test]
+    fn missing_journal_should_restore_nothing() {
+        let journal = TestJournal::new("missing");
+        let pool = Pool::new(Default::default(), TestApi::default());
+        assert_eq!(journal.0.load_at(&pool, &BlockId::number(0)).unwrap(), 0);
+        assert_eq!(pool.light_status().transaction_count, 0);
+    }
+}
@@ -4,7 +4,9 @@ extern crate akro_api;
 extern crate akro_executor;
 extern crate akro_primitives;
 extern crate akro_runtime;
+extern crate futures;
 extern crate parity_codec as codec;
+extern crate parking_lot;
 extern crate sr_primitives as runtime_primitives;
 extern crate substrate_client;
 extern crate substrate_client_db;
@@ -18,9 +20,15 @@ extern crate error_chain;
 #[macro_use]
 extern crate log;
 
+mod config;
 mod error;
+mod journal;
+mod mock;
 mod pool;
 
-pub use extrinsic_pool::Pool;
-pub use pool::PoolApi;
-pub use pool::TransactionPool;
+pub use config::{PoolConfig, DEFAULT_FEE_BUMP};
+pub use error::{Error, ErrorKind, Result};
+pub use extrinsic_pool::{watcher, ExtrinsicFor, LightStatus, Pool};
+pub use journal::Journal;
+pub use pool::{PoolApi, PoolChain};
+pub use pool::{StatusStream, TransactionPool};
@@ -0,0 +1,200 @@
+// Copyright 2018 Akropolis.
+
+//! Test utilities
+
+#![cfg(test)]
+
+use akro_api;
+use akro_primitives::{AccountId, Balance, Block, BlockId, BlockNumber, Hash, Index};
+use akro_runtime::{Address, Call, ConsensusCall, RawAddress, UncheckedExtrinsic};
+use codec::{Decode, Encode};
+use config::DEFAULT_FEE_BUMP;
+use error::{Error, ErrorKind, Result};
+use extrinsic_pool::{scoring, ChainApi, ExtrinsicFor, Pool, Readiness, Transaction, VerifiedFor};
+use parking_lot::Mutex;
+use pool::{replacement_fee, PoolApi, PoolChain, VerifiedExtrinsic};
+use runtime_primitives::generic::Era;
+use runtime_primitives::traits::{
+    BlakeTwo256, BlockNumberToHash, Bounded, CurrentHeight, Hash as HashT,
+};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+/// Chain state of the transaction pool.
+#[derive(Default)]
+pub struct TestChain {
+    /// Number of the best block.
+    pub best: Mutex<BlockNumber>,
+    /// Next index of the senders, 0 for those not in there.
+    pub indices: Mutex<HashMap<AccountId, Index>>,
+    /// Account fees are burned to, `[0; 32]` as on chain.
+    pub death_account: AccountId,
+}
+
+impl CurrentHeight for TestChain {
+    type BlockNumber = BlockNumber;
+    fn current_height(&self) -> BlockNumber {
+        *self.best.lock()
+    }
+}
+
+impl BlockNumberToHash for TestChain {
+    type BlockNumber = BlockNumber;
+    type Hash = Hash;
+    fn block_number_to_hash(&self, _n: BlockNumber) -> Option<Hash> {
+        Some(Default::default())
+    }
+}
+
+impl PoolChain for TestChain {
+    fn lookup(&self, _at: &BlockId, address: Address) -> akro_api::Result<Option<AccountId>> {
+        match address {
+            RawAddress::Id(who) => Ok(Some(who)),
+            RawAddress::Index(_) => Ok(None),
+        }
+    }
+
+    fn index(&self, _at: &BlockId, account: AccountId) -> akro_api::Result<Index> {
+        Ok(self.indices.lock().get(&account).cloned().unwrap_or(0))
+    }
+
+    fn death_account(&self, _at: &BlockId) -> akro_api::Result<AccountId> {
+        Ok(self.death_account)
+    }
+
+    fn extrinsic_fee(
+        &self,
+        _at: &BlockId,
+        _xt: &::akro_primitives::UncheckedExtrinsic,
+    ) -> akro_api::Result<Balance> {
+        Ok(0)
+    }
+}
+
+/// Transaction pool with the readiness and scoring of `PoolApi` on top of a `TestChain`.
+///
+/// Its extrinsics encode the sender, index, fee and end of era of their verified transaction,
+/// so they need no signature.
+pub struct TestApi {
+    pub chain: Arc<TestChain>,
+    pub api: PoolApi<TestChain>,
+}
+
+impl Default for TestApi {
+    fn default() -> Self {
+        let chain = Arc::new(TestChain::default());
+        TestApi {
+            api: PoolApi::new(chain.clone()),
+            chain,
+        }
+    }
+}
+
+type Scoring = PoolApi<TestChain>;
+
+impl ChainApi for TestApi {
+    type Block = Block;
+    type Hash = Hash;
+    type Sender = AccountId;
+    type VEx = VerifiedExtrinsic;
+    type Ready = HashMap<AccountId, u64>;
+    type Error = Error;
+    type Score = Balance;
+    type Event = ();
+
+    fn verify_transaction(&self, _at: &BlockId, xt: &ExtrinsicFor<Self>) -> Result<Self::VEx> {
+        let (sender, index, fee, valid_till): (u8, Index, Balance, BlockNumber) =
+            Decode::decode(&mut &xt.0[..]).ok_or(ErrorKind::InvalidExtrinsicFormat)?;
+        Ok(VerifiedExtrinsic {
+            hash: BlakeTwo256::hash(&xt.encode()),
+            sender: [sender; 32].into(),
+            valid_till,
+            ..verified(index, fee)
+        })
+    }
+
+    fn ready(&self) -> Self::Ready {
+        self.api.ready()
+    }
+
+    fn is_ready(
+        &self,
+        at: &BlockId,
+        known_nonces: &mut Self::Ready,
+        xt: &VerifiedFor<Self>,
+    ) -> Readiness {
+        self.api.is_ready(at, known_nonces, xt)
+    }
+
+    fn compare(old: &VerifiedFor<Self>, other: &VerifiedFor<Self>) -> Ordering {
+        Scoring::compare(old, other)
+    }
+
+    fn choose(old: &VerifiedFor<Self>, new: &VerifiedFor<Self>) -> scoring::Choice {
+        Scoring::choose(old, new)
+    }
+
+    fn update_scores(
+        xts: &[Transaction<VerifiedFor<Self>>],
+        scores: &mut [Self::Score],
+        change: scoring::Change<()>,
+    ) {
+        Scoring::update_scores(xts, scores, change)
+    }
+
+    fn should_replace(old: &VerifiedFor<Self>, new: &VerifiedFor<Self>) -> scoring::Choice {
+        Scoring::should_replace(old, new)
+    }
+
+    fn latest_hash(&self) -> Hash {
+        Default::default()
+    }
+}
+
+pub fn extrinsic(sender: u8, index: Index, fee: Balance) -> ExtrinsicFor<TestApi> {
+    mortal_extrinsic(sender, index, fee, Bounded::max_value())
+}
+
+pub fn mortal_extrinsic(
+    sender: u8,
+    index: Index,
+    fee: Balance,
+    valid_till: BlockNumber,
+) -> ExtrinsicFor<TestApi> {
+    ::akro_primitives::UncheckedExtrinsic((sender, index, fee, valid_till).encode())
+}
+
+/// Extrinsic of the runtime sent by `who`, its signature is not checked before the sender.
+pub fn signed_extrinsic(who: AccountId) -> ::akro_primitives::UncheckedExtrinsic {
+    let xt = UncheckedExtrinsic {
+        signature: Some((RawAddress::Id(who), Default::default(), 0, Era::immortal())),
+        function: Call::Consensus(ConsensusCall::note_offline(vec![])),
+    };
+    ::akro_primitives::UncheckedExtrinsic(xt.encode())
+}
+
+pub fn pending_fees(pool: &Pool<TestApi>) -> Vec<(u8, Index, Balance)> {
+    pool.cull_and_get_pending(&BlockId::number(0), |pending| {
+        pending
+            .map(|t| {
+                (
+                    t.verified.sender.as_bytes()[0],
+                    t.verified.index,
+                    t.verified.fee,
+                )
+            })
+            .collect()
+    })
+    .unwrap()
+}
+
+pub fn verified(index: Index, fee: Balance) -> VerifiedExtrinsic {
+    VerifiedExtrinsic {
+        hash: [index as u8; 32].into(),
+        sender: [1; 32].into(),
+        index,
+        fee,
+        replacement_fee: replacement_fee(fee, DEFAULT_FEE_BUMP),
+        valid_till: Bounded::max_value(),
+        encoded_size: 100,
+    }
+}
@@ -1,21 +1,31 @@
 // Copyright 2018 Akropolis.
 
-use akro_api::AkroApi;
+use akro_api::{self, AkroApi};
 use akro_executor;
-use akro_primitives::{AccountId, Block, BlockId, BlockNumber, Hash, Index};
+use akro_primitives::{AccountId, Balance, Block, BlockId, BlockNumber, Hash, Index};
 use akro_runtime::{Address, UncheckedExtrinsic};
 use codec::{Decode, Encode};
+use config::PoolConfig;
 use error::{Error, ErrorKind, Result};
 use extrinsic_pool;
 use extrinsic_pool::IntoPoolError;
 use extrinsic_pool::{
-    scoring, scoring::Choice, ChainApi, ExtrinsicFor, Options, Pool, Readiness, Transaction,
-    VerifiedFor, VerifiedTransaction,
+    scoring,
+    scoring::Choice,
+    watcher::{Status, Watcher},
+    ChainApi, ExtrinsicFor, Pool, Readiness, Transaction, VerifiedFor, VerifiedTransaction,
 };
+use futures::Stream;
+use parking_lot::Mutex;
 use runtime_primitives::traits::{
     BlakeTwo256, BlockNumberToHash, Bounded, Checkable, CurrentHeight, Hash as HashT, Lookup,
 };
-use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
 use substrate_client::{self, Client};
 use substrate_client_db;
 use substrate_executor::NativeExecutor;
@@ -35,7 +45,13 @@ pub struct VerifiedExtrinsic {
     pub sender: AccountId,
     /// Transaction index.
     pub index: Index,
-    encoded_size: usize,
+    /// Fee the transaction pays.
+    pub fee: Balance,
+    /// Least fee a transaction of the same sender and index must pay to replace this one.
+    pub(crate) replacement_fee: Balance,
+    /// First block the transaction can not be included in, by the era it is signed for.
+    pub valid_till: BlockNumber,
+    pub(crate) encoded_size: usize,
 }
 
 impl VerifiedExtrinsic {
@@ -53,6 +69,50 @@ impl VerifiedExtrinsic {
     pub fn index(&self) -> Index {
         self.index
     }
+
+    /// Get the fee this transaction pays.
+    pub fn fee(&self) -> Balance {
+        self.fee
+    }
+}
+
+/// Whether `new` replaces `old`, a pooled transaction of the same sender.
+///
+/// Transactions of different indices are both kept. Of the same index, `new` must pay at least
+/// the replacement fee of `old`, and strictly more than `old` itself.
+fn choose_by_fee(old: &VerifiedExtrinsic, new: &VerifiedExtrinsic) -> Choice {
+    if old.index != new.index {
+        return Choice::InsertNew;
+    }
+    if new.fee > old.fee && new.fee >= old.replacement_fee {
+        Choice::ReplaceOld
+    } else {
+        Choice::RejectNew
+    }
+}
+
+/// Whether a transaction valid till `valid_till` can not be included after the block `best`.
+pub(crate) fn is_expired(valid_till: BlockNumber, best: BlockNumber) -> bool {
+    valid_till <= best.saturating_add(1)
+}
+
+/// Whether a verification error means the transaction can never become valid, so it is banned
+/// instead of being verified again each time it is gossiped.
+///
+/// Only errors which do not depend on the state are, a lookup or signature failure may be
+/// fixed by a later block.
+fn is_invalid(e: &Error) -> bool {
+    match *e.kind() {
+        ErrorKind::InvalidExtrinsicFormat | ErrorKind::IsInherent(_) | ErrorKind::TooLarge(..) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Least fee which out-pays `fee` by `bump` percent.
+pub(crate) fn replacement_fee(fee: Balance, bump: u32) -> Balance {
+    fee.saturating_add(fee.saturating_mul(Balance::from(bump)) / 100)
 }
 
 impl VerifiedTransaction for VerifiedExtrinsic {
@@ -72,21 +132,64 @@ impl VerifiedTransaction for VerifiedExtrinsic {
     }
 }
 
+/// Chain state transactions are verified and scheduled against.
+pub trait PoolChain:
+    CurrentHeight<BlockNumber = BlockNumber> + BlockNumberToHash<BlockNumber = BlockNumber, Hash = Hash>
+{
+    /// Get the account id of an address at a block.
+    fn lookup(&self, at: &BlockId, address: Address) -> akro_api::Result<Option<AccountId>>;
+
+    /// Get the nonce (né index) of an account at a block.
+    fn index(&self, at: &BlockId, account: AccountId) -> akro_api::Result<Index>;
+
+    /// Get the account fees are burned to at a block, which can not send transactions.
+    fn death_account(&self, at: &BlockId) -> akro_api::Result<AccountId>;
+
+    /// Get the fee an extrinsic would pay at a block.
+    fn extrinsic_fee(
+        &self,
+        at: &BlockId,
+        xt: &::akro_primitives::UncheckedExtrinsic,
+    ) -> akro_api::Result<Balance>;
+}
+
+impl<A: AkroApi> PoolChain for A {
+    fn lookup(&self, at: &BlockId, address: Address) -> akro_api::Result<Option<AccountId>> {
+        AkroApi::lookup(self, at, address)
+    }
+
+    fn index(&self, at: &BlockId, account: AccountId) -> akro_api::Result<Index> {
+        AkroApi::index(self, at, account)
+    }
+
+    fn death_account(&self, at: &BlockId) -> akro_api::Result<AccountId> {
+        AkroApi::death_account(self, at)
+    }
+
+    fn extrinsic_fee(
+        &self,
+        at: &BlockId,
+        xt: &::akro_primitives::UncheckedExtrinsic,
+    ) -> akro_api::Result<Balance> {
+        AkroApi::extrinsic_fee(self, at, xt)
+    }
+}
+
 pub struct LocalContext<'a, A: 'a>(&'a Arc<A>);
-impl<'a, A: 'a + AkroApi> CurrentHeight for LocalContext<'a, A> {
+impl<'a, A: 'a + PoolChain> CurrentHeight for LocalContext<'a, A> {
     type BlockNumber = BlockNumber;
     fn current_height(&self) -> BlockNumber {
         self.0.current_height()
     }
 }
-impl<'a, A: 'a + AkroApi> BlockNumberToHash for LocalContext<'a, A> {
+impl<'a, A: 'a + PoolChain> BlockNumberToHash for LocalContext<'a, A> {
     type BlockNumber = BlockNumber;
     type Hash = Hash;
     fn block_number_to_hash(&self, n: BlockNumber) -> Option<Hash> {
         self.0.block_number_to_hash(n)
     }
 }
-impl<'a, A: 'a + AkroApi> Lookup for LocalContext<'a, A> {
+impl<'a, A: 'a + PoolChain> Lookup for LocalContext<'a, A> {
     type Source = Address;
     type Target = AccountId;
     fn lookup(&self, a: Address) -> ::std::result::Result<AccountId, &'static str> {
@@ -97,35 +200,142 @@ impl<'a, A: 'a + AkroApi> Lookup for LocalContext<'a, A> {
     }
 }
 
+/// Statuses of a watched transaction.
+pub type StatusStream = Box<Stream<Item = Status<Hash>, Error = ()> + Send>;
+
+/// Watched transactions, and those of them found expired.
+#[derive(Default)]
+pub(crate) struct Watched {
+    /// Number of status streams of each watched transaction.
+    watchers: HashMap<Hash, usize>,
+    /// Watched transactions whose era is over, the pool culls them as if they were included.
+    expired: HashSet<Hash>,
+}
+
+impl Watched {
+    pub(crate) fn mark_expired(&mut self, hash: Hash) {
+        if self.watchers.contains_key(&hash) {
+            self.expired.insert(hash);
+        }
+    }
+}
+
+/// Registration of a status stream of a transaction, dropped with the stream.
+struct WatchGuard {
+    hash: Hash,
+    watched: Arc<Mutex<Watched>>,
+}
+
+impl WatchGuard {
+    fn new(watched: Arc<Mutex<Watched>>, hash: Hash) -> Self {
+        *watched.lock().watchers.entry(hash).or_insert(0) += 1;
+        WatchGuard { hash, watched }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.watched.lock().expired.contains(&self.hash)
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        let mut watched = self.watched.lock();
+        let unwatched = match watched.watchers.get_mut(&self.hash) {
+            Some(count) => {
+                *count -= 1;
+                *count == 0
+            }
+            None => false,
+        };
+        if unwatched {
+            watched.watchers.remove(&self.hash);
+            watched.expired.remove(&self.hash);
+        }
+    }
+}
+
+/// Statuses of `watcher`, with the culling of an expired transaction reported as dropped.
+fn status_stream(watcher: Watcher<Hash>, guard: WatchGuard) -> StatusStream {
+    Box::new(watcher.into_stream().map(move |status| match status {
+        Status::Finalised(_) if guard.is_expired() => Status::Dropped,
+        status => status,
+    }))
+}
+
+/// Watch the status of a transaction already in `pool`.
+///
+/// The pool hands out watchers only on import and can not attach one to a pooled transaction.
+/// Taking it out and importing it again would lose its place and may lose the transaction, so
+/// this is refused, leaving the pool untouched. Transactions which are not ready are reported
+/// unknown.
+fn watch_pooled<B>(pool: &Pool<B>, at: &BlockId, hash: Hash) -> Result<StatusStream>
+where
+    B: ChainApi<Block = Block, Hash = Hash>,
+{
+    if pool.pending(at, |mut pending| pending.any(|t| *t.hash() == hash)) {
+        bail!(ErrorKind::WatchUnsupported(hash))
+    } else {
+        bail!(ErrorKind::UnknownTransaction(hash))
+    }
+}
+
 pub struct PoolApi<A> {
     api: Arc<A>,
+    config: PoolConfig,
+    /// Transactions rejected as invalid, with the end of their ban.
+    banned: Mutex<HashMap<Hash, Instant>>,
+    /// Watched transactions, shared with the transaction pool.
+    pub(crate) watched: Arc<Mutex<Watched>>,
 }
 
 impl<A> PoolApi<A>
 where
-    A: AkroApi,
+    A: PoolChain,
 {
     /// Create a new instance.
     pub fn new(api: Arc<A>) -> Self {
-        PoolApi { api }
+        PoolApi {
+            api,
+            config: Default::default(),
+            banned: Default::default(),
+            watched: Default::default(),
+        }
     }
-}
 
-impl<A> ChainApi for PoolApi<A>
-where
-    A: AkroApi + Send + Sync,
-{
-    type Block = Block;
-    type Hash = Hash;
-    type Sender = AccountId;
-    type VEx = VerifiedExtrinsic;
-    type Ready = HashMap<AccountId, u64>;
-    type Error = Error;
-    type Score = u64;
-    type Event = ();
+    /// Apply the fee bump, future limit and ban time of `config`.
+    pub fn with_config(mut self, config: PoolConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-    fn verify_transaction(&self, _at: &BlockId, xt: &ExtrinsicFor<Self>) -> Result<Self::VEx> {
-        let encoded = xt.encode();
+    /// Whether the transaction was rejected as invalid less than the ban time ago.
+    fn is_banned(&self, hash: &Hash) -> bool {
+        let mut banned = self.banned.lock();
+        match banned.get(hash).cloned() {
+            Some(until) if until > Instant::now() => true,
+            Some(_) => {
+                banned.remove(hash);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Refuse the transaction for the ban time, dropping the bans which are over.
+    fn ban(&self, hash: Hash) {
+        let now = Instant::now();
+        let mut banned = self.banned.lock();
+        banned.retain(|_, until| *until > now);
+        banned.insert(hash, now + self.config.ban_time);
+    }
+
+    fn verify(
+        &self,
+        at: &BlockId,
+        xt: &::akro_primitives::UncheckedExtrinsic,
+        encoded: Vec<u8>,
+        hash: Hash,
+    ) -> Result<VerifiedExtrinsic> {
         let uxt = UncheckedExtrinsic::decode(&mut encoded.as_slice())
             .ok_or_else(|| ErrorKind::InvalidExtrinsicFormat)?;
 
@@ -133,30 +343,80 @@ where
             bail!(ErrorKind::IsInherent(uxt))
         }
 
-        let (encoded_size, hash) = (encoded.len(), BlakeTwo256::hash(&encoded));
+        let encoded_size = encoded.len();
         if encoded_size > MAX_TRANSACTION_SIZE {
             bail!(ErrorKind::TooLarge(encoded_size, MAX_TRANSACTION_SIZE));
         }
 
         debug!(target: "transaction-pool", "Transaction submitted: {}", ::substrate_primitives::hexdisplay::HexDisplay::from(&encoded));
+        // signatures of the death account can be forged, it is refused before checking them
+        if let Some((ref address, ..)) = uxt.signature {
+            if self.api.lookup(at, address.clone())? == Some(self.api.death_account(at)?) {
+                bail!(ErrorKind::DeathAccount);
+            }
+        }
+        let current = self.api.current_height();
+        let valid_till = uxt
+            .signature
+            .as_ref()
+            .map_or(Bounded::max_value(), |&(_, _, _, era)| era.death(current));
         let checked = uxt.clone().check(&LocalContext(&self.api))?;
         let (sender, index) = checked
             .signed
             .expect("function previously bailed unless uxt.is_signed(); qed");
 
+        let next_index = self.api.index(at, sender)?;
+        if index.saturating_sub(next_index) > self.config.max_future {
+            bail!(ErrorKind::TooFarInFuture(index, next_index));
+        }
+
         if encoded_size < 1024 {
             debug!(target: "transaction-pool", "Transaction verified: {} => {:?}", hash, uxt);
         } else {
             debug!(target: "transaction-pool", "Transaction verified: {} ({} bytes is too large to display)", hash, encoded_size);
         }
 
+        let fee = self.api.extrinsic_fee(at, xt)?;
         Ok(VerifiedExtrinsic {
             index,
             sender,
             hash,
+            fee,
+            replacement_fee: replacement_fee(fee, self.config.fee_bump),
+            valid_till,
             encoded_size,
         })
     }
+}
+
+impl<A> ChainApi for PoolApi<A>
+where
+    A: PoolChain + Send + Sync,
+{
+    type Block = Block;
+    type Hash = Hash;
+    type Sender = AccountId;
+    type VEx = VerifiedExtrinsic;
+    type Ready = HashMap<AccountId, u64>;
+    type Error = Error;
+    type Score = Balance;
+    type Event = ();
+
+    fn verify_transaction(&self, at: &BlockId, xt: &ExtrinsicFor<Self>) -> Result<Self::VEx> {
+        let encoded = xt.encode();
+        let hash = BlakeTwo256::hash(&encoded);
+        if self.is_banned(&hash) {
+            bail!(ErrorKind::TemporarilyBanned(hash));
+        }
+
+        self.verify(at, xt, encoded, hash).map_err(|e| {
+            if is_invalid(&e) {
+                debug!(target: "transaction-pool", "Banning invalid transaction {}: {}", hash, e);
+                self.ban(hash);
+            }
+            e
+        })
+    }
 
     fn ready(&self) -> Self::Ready {
         HashMap::default()
@@ -171,6 +431,13 @@ where
         let sender = xt.verified.sender().clone();
         trace!(target: "transaction-pool", "Checking readiness of {} (from {})", xt.verified.hash, sender);
 
+        // a transaction whose era is over can not be included any more, whatever its index
+        if is_expired(xt.verified.valid_till, self.api.current_height()) {
+            debug!(target: "transaction-pool", "Transaction {} expired at block #{}", xt.verified.hash, xt.verified.valid_till);
+            self.watched.lock().mark_expired(xt.verified.hash);
+            return Readiness::Stale;
+        }
+
         // TODO: find a way to handle index error properly -- will need changes to
         // transaction-pool trait.
         let api = &self.api;
@@ -183,7 +450,6 @@ where
         let result = match xt.verified.index.cmp(&next_index) {
             Ordering::Greater => Readiness::Future,
             Ordering::Equal => Readiness::Ready,
-            // TODO [ToDr] Should mark transactions referencing too old blockhash as `Stale` as well.
             Ordering::Less => Readiness::Stale,
         };
 
@@ -197,10 +463,7 @@ where
     }
 
     fn choose(old: &VerifiedFor<Self>, new: &VerifiedFor<Self>) -> scoring::Choice {
-        if old.verified.index() == new.verified.index() {
-            return Choice::ReplaceOld;
-        }
-        Choice::InsertNew
+        choose_by_fee(&old.verified, &new.verified)
     }
 
     fn update_scores(
@@ -208,13 +471,19 @@ where
         scores: &mut [Self::Score],
         _change: scoring::Change<()>,
     ) {
-        for i in 0..xts.len() {
-            scores[i] = 1;
+        // the pending set is yielded by descending score, so blocks take the best paying first
+        for (score, xt) in scores.iter_mut().zip(xts) {
+            *score = xt.verified.fee();
         }
     }
 
-    fn should_replace(_old: &VerifiedFor<Self>, _new: &VerifiedFor<Self>) -> scoring::Choice {
-        Choice::RejectNew
+    fn should_replace(old: &VerifiedFor<Self>, new: &VerifiedFor<Self>) -> scoring::Choice {
+        // a full pool makes room by dropping a transaction paying less
+        if new.verified.fee() > old.verified.fee() {
+            Choice::ReplaceOld
+        } else {
+            Choice::RejectNew
+        }
     }
 
     fn latest_hash(&self) -> Hash {
@@ -230,6 +499,7 @@ where
 {
     inner: Arc<Pool<PoolApi<A>>>,
     client: Arc<Client<Backend, Executor, Block>>,
+    watched: Arc<Mutex<Watched>>,
 }
 
 impl<A> TransactionPool<A>
@@ -238,16 +508,43 @@ where
 {
     /// Create a new transaction pool.
     pub fn new(
-        options: Options,
+        config: PoolConfig,
         api: PoolApi<A>,
         client: Arc<Client<Backend, Executor, Block>>,
     ) -> Self {
+        let options = config.options();
+        let api = api.with_config(config);
         TransactionPool {
+            watched: api.watched.clone(),
             inner: Arc::new(Pool::new(options, api)),
             client,
         }
     }
 
+    /// Remove the transactions which can not be included on top of the block `at` any more.
+    ///
+    /// Watchers of the transactions whose era is over are told they were dropped.
+    pub fn cull(&self, at: &BlockId) -> Result<usize> {
+        self.inner.cull(at)
+    }
+
+    /// Import a transaction on top of the block `at` and watch its status.
+    pub fn submit_and_watch(
+        &self,
+        at: &BlockId,
+        xt: ExtrinsicFor<PoolApi<A>>,
+    ) -> Result<StatusStream> {
+        // registered first, the transaction may expire as soon as it is imported
+        let guard = WatchGuard::new(self.watched.clone(), BlakeTwo256::hash(&xt.encode()));
+        let watcher = self.inner.submit_and_watch(at, xt)?;
+        Ok(status_stream(watcher, guard))
+    }
+
+    /// Watch the status of a transaction already in the pool, see `watch_pooled`.
+    pub fn watch(&self, at: &BlockId, hash: Hash) -> Result<StatusStream> {
+        watch_pooled(&self.inner, at, hash)
+    }
+
     pub fn best_block_id(&self) -> Option<BlockId> {
         self.client
             .info()
@@ -258,6 +555,19 @@ where
     pub fn inner(&self) -> Arc<Pool<PoolApi<A>>> {
         self.inner.clone()
     }
+
+    /// Index the next transaction of `sender` should use on top of the block `at`, after its
+    /// ready transactions in the pool.
+    pub fn next_index(&self, at: &BlockId, sender: AccountId) -> Result<Index> {
+        let chain_index = AkroApi::index(&*self.client, at, sender)?;
+        let pending_index = self.inner.cull_and_get_pending(at, |pending| {
+            pending
+                .filter(|t| t.verified.sender == sender)
+                .map(|t| t.verified.index() + 1)
+                .max()
+        })?;
+        Ok(pending_index.map_or(chain_index, |index| ::std::cmp::max(index, chain_index)))
+    }
 }
 
 impl<A> substrate_network::TransactionPool<Hash, Block> for TransactionPool<A>
@@ -318,3 +628,285 @@ where
         self.inner.on_broadcasted(propagations)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{
+        extrinsic, mortal_extrinsic, pending_fees, signed_extrinsic, verified, TestApi, TestChain,
+    };
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn replacement_fee_should_add_the_bump() {
+        assert_eq!(replacement_fee(100, 10), 110);
+        assert_eq!(replacement_fee(100, 0), 100);
+        assert_eq!(replacement_fee(0, 10), 0);
+        assert_eq!(
+            replacement_fee(Balance::max_value(), 10),
+            Balance::max_value()
+        );
+    }
+
+    #[test]
+    fn other_index_should_be_inserted() {
+        assert_eq!(
+            choose_by_fee(&verified(1, 100), &verified(2, 1)),
+            Choice::InsertNew
+        );
+        assert_eq!(
+            choose_by_fee(&verified(2, 100), &verified(1, 1000)),
+            Choice::InsertNew
+        );
+    }
+
+    #[test]
+    fn same_index_should_replace_only_with_the_bump() {
+        let old = verified(1, 100);
+        assert_eq!(choose_by_fee(&old, &verified(1, 100)), Choice::RejectNew);
+        assert_eq!(choose_by_fee(&old, &verified(1, 109)), Choice::RejectNew);
+        assert_eq!(choose_by_fee(&old, &verified(1, 110)), Choice::ReplaceOld);
+        assert_eq!(choose_by_fee(&old, &verified(1, 50)), Choice::RejectNew);
+    }
+
+    #[test]
+    fn mortal_transaction_should_expire_with_its_era() {
+        use runtime_primitives::generic::Era;
+
+        // signed at #100 for 64 blocks
+        let valid_till = Era::mortal(64, 100).death(100);
+        assert!(valid_till > 101);
+        assert!(!is_expired(valid_till, 100));
+        assert!(!is_expired(valid_till, valid_till - 2));
+        // the next block would be past the era
+        assert!(is_expired(valid_till, valid_till - 1));
+        assert!(is_expired(valid_till, valid_till + 10));
+
+        let immortal = Era::Immortal.death(100);
+        assert!(!is_expired(immortal, 1_000_000));
+    }
+
+    #[test]
+    fn only_invalid_transactions_should_be_banned() {
+        assert!(is_invalid(&ErrorKind::InvalidExtrinsicFormat.into()));
+        assert!(is_invalid(&ErrorKind::TooLarge(2, 1).into()));
+        // depend on the state, the address may be assigned or the signature checked against
+        // another account by a later block
+        assert!(!is_invalid(
+            &ErrorKind::BadSignature("bad signature").into()
+        ));
+        assert!(!is_invalid(&"error with lookup".into()));
+        assert!(!is_invalid(
+            &ErrorKind::UnrecognisedAddress(Address::Index(1)).into()
+        ));
+        // may be valid once the sender's earlier transactions are included
+        assert!(!is_invalid(&ErrorKind::TooFarInFuture(100, 1).into()));
+        assert!(!is_invalid(
+            &ErrorKind::TemporarilyBanned([1; 32].into()).into()
+        ));
+    }
+
+    #[test]
+    fn pending_transactions_should_be_ordered_by_fee() {
+        let pool = Pool::new(Default::default(), TestApi::default());
+        let at = BlockId::number(0);
+        for xt in vec![
+            extrinsic(1, 0, 10),
+            extrinsic(2, 0, 30),
+            extrinsic(3, 0, 20),
+            extrinsic(1, 1, 50),
+        ] {
+            pool.submit_one(&at, xt).unwrap();
+        }
+
+        // the best paying first, after the earlier transactions of its sender
+        assert_eq!(
+            pending_fees(&pool),
+            vec![(2, 0, 30), (3, 0, 20), (1, 0, 10), (1, 1, 50)]
+        );
+    }
+
+    #[test]
+    fn pooled_transaction_should_be_replaced_only_with_the_bump() {
+        let pool = Pool::new(Default::default(), TestApi::default());
+        let at = BlockId::number(0);
+        pool.submit_one(&at, extrinsic(1, 0, 100)).unwrap();
+
+        assert!(pool.submit_one(&at, extrinsic(1, 0, 109)).is_err());
+        assert_eq!(pending_fees(&pool), vec![(1, 0, 100)]);
+
+        pool.submit_one(&at, extrinsic(1, 0, 110)).unwrap();
+        assert_eq!(pending_fees(&pool), vec![(1, 0, 110)]);
+        assert_eq!(pool.light_status().transaction_count, 1);
+    }
+
+    #[test]
+    fn expired_transaction_should_be_culled_and_reported_dropped() {
+        let api = TestApi::default();
+        let (chain, watched) = (api.chain.clone(), api.api.watched.clone());
+        let pool = Pool::new(Default::default(), api);
+        let at = BlockId::number(0);
+
+        let xt = mortal_extrinsic(1, 0, 10, 5);
+        let guard = WatchGuard::new(watched.clone(), BlakeTwo256::hash(&xt.encode()));
+        let statuses = status_stream(pool.submit_and_watch(&at, xt).unwrap(), guard);
+        pool.submit_one(&at, extrinsic(2, 0, 10)).unwrap();
+        assert_eq!(pending_fees(&pool), vec![(1, 0, 10), (2, 0, 10)]);
+
+        // the next block would be past the era
+        *chain.best.lock() = 4;
+        assert_eq!(pool.cull(&at).unwrap(), 1);
+        assert_eq!(pending_fees(&pool), vec![(2, 0, 10)]);
+
+        let done = statuses
+            .wait()
+            .map(|status| status.unwrap())
+            .find(|status| match *status {
+                Status::Finalised(_) | Status::Usurped(_) | Status::Dropped => true,
+                _ => false,
+            });
+        match done {
+            Some(Status::Dropped) => {}
+            status => panic!("expected the transaction to be dropped, got {:?}", status),
+        }
+
+        // the stream is over, nothing is watched any more
+        let watched = watched.lock();
+        assert!(watched.watchers.is_empty());
+        assert!(watched.expired.is_empty());
+    }
+
+    #[test]
+    fn transaction_should_be_ready_at_the_next_index_of_its_sender() {
+        let api = TestApi::default();
+        let chain = api.chain.clone();
+        let pool = Pool::new(Default::default(), api);
+        let at = BlockId::number(0);
+
+        pool.submit_one(&at, extrinsic(1, 1, 20)).unwrap();
+        pool.submit_one(&at, extrinsic(2, 0, 10)).unwrap();
+        assert_eq!(pending_fees(&pool), vec![(2, 0, 10)]);
+
+        // index 0 of the first sender got included
+        chain.indices.lock().insert([1; 32].into(), 1);
+        assert_eq!(pending_fees(&pool), vec![(1, 1, 20), (2, 0, 10)]);
+
+        // and the second sender's transaction too, it is stale now
+        chain.indices.lock().insert([2; 32].into(), 1);
+        assert_eq!(pool.cull(&at).unwrap(), 1);
+        assert_eq!(pending_fees(&pool), vec![(1, 1, 20)]);
+    }
+
+    #[test]
+    fn watching_a_pooled_transaction_should_leave_the_pool_unchanged() {
+        let pool = Pool::new(Default::default(), TestApi::default());
+        let at = BlockId::number(0);
+        let xt = extrinsic(1, 0, 10);
+        let hash = BlakeTwo256::hash(&xt.encode());
+        pool.submit_one(&at, xt).unwrap();
+        pool.submit_one(&at, extrinsic(2, 0, 20)).unwrap();
+        pool.submit_one(&at, extrinsic(1, 2, 30)).unwrap();
+
+        let err = watch_pooled(&pool, &at, hash).err().unwrap();
+        match *err.kind() {
+            ErrorKind::WatchUnsupported(h) if h == hash => {}
+            ref e => panic!("expected watching to be unsupported, got {:?}", e),
+        }
+        assert_eq!(pending_fees(&pool), vec![(2, 0, 20), (1, 0, 10)]);
+        assert_eq!(pool.light_status().transaction_count, 3);
+
+        let err = watch_pooled(&pool, &at, [9; 32].into()).err().unwrap();
+        match *err.kind() {
+            ErrorKind::UnknownTransaction(_) => {}
+            ref e => panic!("expected an unknown transaction, got {:?}", e),
+        }
+    }
+
+    fn banning_api(ban_time: Duration) -> PoolApi<TestChain> {
+        PoolApi::new(Arc::new(TestChain::default())).with_config(PoolConfig {
+            ban_time,
+            ..Default::default()
+        })
+    }
+
+    fn assert_kind(result: Result<VerifiedExtrinsic>, check: fn(&ErrorKind) -> bool) {
+        match result {
+            Err(ref e) if check(e.kind()) => {}
+            r => panic!("unexpected verification result {:?}", r.map(|xt| xt.hash)),
+        }
+    }
+
+    fn is_format_error(e: &ErrorKind) -> bool {
+        match *e {
+            ErrorKind::InvalidExtrinsicFormat => true,
+            _ => false,
+        }
+    }
+
+    fn is_ban(e: &ErrorKind) -> bool {
+        match *e {
+            ErrorKind::TemporarilyBanned(_) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn invalid_transaction_should_be_banned_for_the_ban_time() {
+        let api = banning_api(Duration::from_millis(50));
+        let at = BlockId::number(0);
+        let invalid = ::akro_primitives::UncheckedExtrinsic(vec![1]);
+
+        assert_kind(api.verify_transaction(&at, &invalid), is_format_error);
+        // refused without being verified again
+        assert_kind(api.verify_transaction(&at, &invalid), is_ban);
+
+        // verified again once the ban is over, and banned again
+        thread::sleep(Duration::from_millis(60));
+        assert_kind(api.verify_transaction(&at, &invalid), is_format_error);
+        assert_kind(api.verify_transaction(&at, &invalid), is_ban);
+    }
+
+    #[test]
+    fn ban_should_drop_the_bans_which_are_over() {
+        let api = banning_api(Duration::from_millis(50));
+        let at = BlockId::number(0);
+        let first = ::akro_primitives::UncheckedExtrinsic(vec![1]);
+        let second = ::akro_primitives::UncheckedExtrinsic(vec![2]);
+
+        assert_kind(api.verify_transaction(&at, &first), is_format_error);
+        assert_eq!(api.banned.lock().len(), 1);
+
+        thread::sleep(Duration::from_millis(60));
+        assert_kind(api.verify_transaction(&at, &second), is_format_error);
+        let banned = api.banned.lock();
+        assert_eq!(banned.len(), 1);
+        assert!(banned.contains_key(&BlakeTwo256::hash(&second.encode())));
+    }
+
+    #[test]
+    fn transaction_of_the_death_account_should_be_refused() {
+        let api = PoolApi::new(Arc::new(TestChain::default()));
+        let at = BlockId::number(0);
+        let death_account = AccountId::default();
+
+        let err = api
+            .verify_transaction(&at, &signed_extrinsic(death_account))
+            .err()
+            .unwrap();
+        match *err.kind() {
+            ErrorKind::DeathAccount => {}
+            ref e => panic!("expected the death account to be refused, got {:?}", e),
+        }
+        // depends on the state, not banned
+        assert!(!api.is_banned(&BlakeTwo256::hash(
+            &signed_extrinsic(death_account).encode()
+        )));
+    }
+
+    #[test]
+    fn free_transaction_should_be_replaced_by_a_paying_one() {
+        let old = verified(1, 0);
+        assert_eq!(choose_by_fee(&old, &verified(1, 0)), Choice::RejectNew);
+        assert_eq!(choose_by_fee(&old, &verified(1, 1)), Choice::ReplaceOld);
+    }
+}
@@ -0,0 +1,57 @@
+use akro_api;
+use akro_pool;
+use jsonrpc_core as rpccore;
+
+error_chain! {
+    links {
+        Api(akro_api::Error, akro_api::ErrorKind) #[doc = "Akro API error"];
+        Pool(akro_pool::Error, akro_pool::ErrorKind) #[doc = "Pool error"];
+    }
+    errors {
+        /// The best block is not known
+        UnknownBlock {
+            description("unknown block"),
+            display("Unknown block"),
+        }
+        /// Neither an SS58 address nor an account index
+        InvalidAddress(address: String) {
+            description("invalid address"),
+            display("Invalid address {}, expected an SS58 address or an account index", address),
+        }
+        /// An account index which is not assigned
+        UnknownAccount(address: String) {
+            description("unknown account"),
+            display("Unknown account {}", address),
+        }
+    }
+}
+
+impl From<Error> for rpccore::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error(ErrorKind::UnknownBlock, _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Unknown block".into(),
+                data: None,
+            },
+            Error(ErrorKind::InvalidAddress(address), _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Invalid address".into(),
+                data: Some(address.into()),
+            },
+            Error(ErrorKind::UnknownAccount(address), _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Unknown account".into(),
+                data: Some(address.into()),
+            },
+            e => {
+                warn!("Unknown error: {:?}", e);
+                rpccore::Error {
+                    code: rpccore::ErrorCode::InternalError,
+                    message: "Unknown error occured".into(),
+                    data: Some(format!("{:?}", e).into()),
+                }
+            }
+        }
+    }
+}
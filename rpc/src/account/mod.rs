@@ -0,0 +1,109 @@
+// Copyright 2018 Akropolis.
+
+//! Account RPC, balances and the next transaction index of an account.
+
+use akro_api::AkroApi;
+use akro_pool::TransactionPool;
+use akro_primitives::{AccountId, AccountIndex, BlockId, Index};
+use akro_runtime::Address;
+use primitives::ed25519::Public;
+use std::sync::Arc;
+
+mod error;
+mod types;
+
+use self::error::{ErrorKind, Result};
+pub use self::types::{AccountInfo, TokenBalanceInfo};
+
+#[cfg(test)]
+mod tests;
+
+build_rpc_trait! {
+    pub trait AccountApi {
+        /// Balances and next transaction index of an SS58 address or an account index.
+        #[rpc(name = "account_info")]
+        fn info(&self, String) -> Result<AccountInfo>;
+
+        /// Next transaction index of an SS58 address or an account index, after its ready
+        /// transactions in the pool.
+        #[rpc(name = "account_nextIndex")]
+        fn next_index(&self, String) -> Result<Index>;
+    }
+}
+
+pub struct Account<C, P>
+where
+    P: AkroApi + Send + Sync,
+{
+    client: Arc<C>,
+    pool: Arc<TransactionPool<P>>,
+}
+
+impl<C, P> Account<C, P>
+where
+    C: AkroApi,
+    P: AkroApi + Send + Sync,
+{
+    pub fn new(client: Arc<C>, pool: Arc<TransactionPool<P>>) -> Self {
+        Self { client, pool }
+    }
+
+    /// The best block of the pool, so that balances and pool indices are read at the same block.
+    fn best_block(&self) -> Result<BlockId> {
+        Ok(self.pool.best_block_id().ok_or(ErrorKind::UnknownBlock)?)
+    }
+
+    /// The account of an SS58 address or of an account index at the block `at`.
+    fn account(&self, at: &BlockId, address: String) -> Result<AccountId> {
+        let raw = parse_address(&address)?;
+        match self.client.lookup(at, raw)? {
+            Some(account) => Ok(account),
+            None => Err(ErrorKind::UnknownAccount(address).into()),
+        }
+    }
+}
+
+/// An account index, or else an SS58 address.
+fn parse_address(address: &str) -> Result<Address> {
+    match address.parse::<AccountIndex>() {
+        Ok(index) => Ok(Address::Index(index)),
+        Err(_) => match Public::from_ss58check(address) {
+            Ok(public) => Ok(Address::Id(public.0.into())),
+            Err(_) => Err(ErrorKind::InvalidAddress(address.to_owned()).into()),
+        },
+    }
+}
+
+impl<C, P> AccountApi for Account<C, P>
+where
+    C: AkroApi + Send + Sync + 'static,
+    P: AkroApi + Send + Sync + 'static,
+{
+    fn info(&self, address: String) -> Result<AccountInfo> {
+        let at = self.best_block()?;
+        let account = self.account(&at, address)?;
+        let (free, reserved, staking_locked, frozen) =
+            self.client.account_balances(&at, account)?;
+        let tokens = self
+            .client
+            .token_balances(&at, account)?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(AccountInfo {
+            account,
+            free,
+            reserved,
+            staking_locked,
+            frozen,
+            tokens,
+            next_index: self.pool.next_index(&at, account)?,
+        })
+    }
+
+    fn next_index(&self, address: String) -> Result<Index> {
+        let at = self.best_block()?;
+        let account = self.account(&at, address)?;
+        Ok(self.pool.next_index(&at, account)?)
+    }
+}
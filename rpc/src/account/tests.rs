@@ -0,0 +1,57 @@
+// Copyright 2018 Akropolis.
+
+use super::error::Error;
+use super::*;
+use jsonrpc_core as rpccore;
+
+fn invalid_params(err: Error) -> bool {
+    rpccore::Error::from(err).code == rpccore::ErrorCode::InvalidParams
+}
+
+#[test]
+fn parse_address_should_accept_account_indices() {
+    assert_eq!(parse_address("0").unwrap(), Address::Index(0));
+    assert_eq!(parse_address("42").unwrap(), Address::Index(42));
+}
+
+#[test]
+fn parse_address_should_accept_ss58_addresses() {
+    let address = "5DMo9Nn6MPEWUDefRwVSUtRp4kVguvBNgNatEgyhDJ32Zakt";
+    let public = Public::from_ss58check(address).unwrap();
+    assert_eq!(
+        parse_address(address).unwrap(),
+        Address::Id(public.0.into())
+    );
+}
+
+#[test]
+fn parse_address_should_reject_invalid_addresses() {
+    for address in &[
+        "",
+        "-1",
+        "not an address",
+        "5DMo9Nn6MPEWUDefRwVSUtRp4kVguvBNgNatEgyhDJ32Zak",
+    ] {
+        let err = parse_address(address).err().unwrap();
+        match *err.kind() {
+            ErrorKind::InvalidAddress(ref a) => assert_eq!(a, address),
+            _ => panic!("unexpected error {:?}", err),
+        }
+        assert!(invalid_params(err));
+    }
+}
+
+#[test]
+fn unknown_accounts_and_blocks_should_be_invalid_params() {
+    assert!(invalid_params(
+        ErrorKind::UnknownAccount("42".into()).into()
+    ));
+    assert!(invalid_params(ErrorKind::UnknownBlock.into()));
+}
+
+#[test]
+fn token_balance_info_should_decode_symbol() {
+    let info = TokenBalanceInfo::from((b"BTC".to_vec(), 5));
+    assert_eq!(info.symbol, "BTC");
+    assert_eq!(info.balance, 5);
+}
@@ -0,0 +1,39 @@
+// Copyright 2018 Akropolis.
+
+//! Types returned by the account RPC.
+
+use akro_api::{Symbol, TokenBalance};
+use akro_primitives::{AccountId, Balance, Index};
+
+/// Balance of an account in a token.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalanceInfo {
+    pub symbol: String,
+    pub balance: TokenBalance,
+}
+
+impl From<(Symbol, TokenBalance)> for TokenBalanceInfo {
+    fn from((symbol, balance): (Symbol, TokenBalance)) -> Self {
+        TokenBalanceInfo {
+            symbol: String::from_utf8_lossy(&symbol).into_owned(),
+            balance,
+        }
+    }
+}
+
+/// Balances and nonce of an account at the best block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    pub account: AccountId,
+    pub free: Balance,
+    pub reserved: Balance,
+    /// Balance locked by the nominations of the account.
+    pub staking_locked: Balance,
+    /// Start-up stake of an intention which can not be unnominated yet.
+    pub frozen: Balance,
+    pub tokens: Vec<TokenBalanceInfo>,
+    /// Index of the next transaction, after the ready transactions of the account in the pool.
+    pub next_index: Index,
+}
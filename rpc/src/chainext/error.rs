@@ -1,3 +1,4 @@
+use akro_indexer;
 use client;
 use jsonrpc_core as rpccore;
 
@@ -21,6 +22,7 @@ pub fn internal<E: ::std::fmt::Debug>(e: E) -> rpccore::Error {
 error_chain! {
     links {
         Client(client::error::Error, client::error::ErrorKind) #[doc = "Client error"];
+        Indexer(akro_indexer::Error, akro_indexer::ErrorKind) #[doc = "Indexer error"];
     }
     errors {
         /// Not implemented yet
@@ -28,6 +30,26 @@ error_chain! {
             description("not yet implemented"),
             display("Method Not Implemented"),
         }
+        /// An extrinsic or event the runtime could not decode
+        Undecodable(what: &'static str) {
+            description("undecodable data"),
+            display("Undecodable {}", what),
+        }
+        /// A block range which ends before it starts
+        InvalidRange {
+            description("invalid block range"),
+            display("Invalid block range, `from` is after `to`"),
+        }
+        /// A block range request for no blocks
+        ZeroLimit {
+            description("zero block limit"),
+            display("Invalid block limit, at least one block must be requested"),
+        }
+        /// The node does not index account history
+        IndexerDisabled {
+            description("account indexer disabled"),
+            display("Account indexer is not enabled, start the node with --indexer"),
+        }
     }
 }
 
@@ -35,6 +57,21 @@ impl From<Error> for rpccore::Error {
     fn from(e: Error) -> Self {
         match e {
             Error(ErrorKind::Unimplemented, _) => unimplemented(),
+            Error(ErrorKind::InvalidRange, _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Invalid block range".into(),
+                data: None,
+            },
+            Error(ErrorKind::ZeroLimit, _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Invalid block limit".into(),
+                data: None,
+            },
+            Error(ErrorKind::IndexerDisabled, _) => rpccore::Error {
+                code: rpccore::ErrorCode::ServerError(2),
+                message: "Account indexer is not enabled".into(),
+                data: None,
+            },
             e => internal(e),
         }
     }
@@ -1,31 +1,247 @@
-use jsonrpc_macros::Trailing;
+use akro_indexer::{AccountHistory, HistoryEntry};
+use akro_primitives::AccountId;
+use akro_runtime::{Call, Event, UncheckedExtrinsic};
+use apis::metadata::Metadata;
+use apis::Subscriptions;
+use codec::{Decode, Encode};
+use futures::{Future, Sink, Stream};
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_macros::{pubsub, Trailing};
+use primitives::hashing::twox_128;
+use primitives::storage::StorageKey;
 use primitives::Blake2Hasher;
+use pubsub::SubscriptionId;
 use runtime_primitives::generic::{BlockId, SignedBlock};
-use runtime_primitives::traits::{Block as BlockT, Header, NumberFor};
+use runtime_primitives::traits::{
+    BlakeTwo256, Block as BlockT, Hash as HashT, Header, NumberFor, One,
+};
+use serde::Serialize;
+use serde_json::{self, Value};
+use srml_system::{self as system, EventRecord, Phase};
 
 use client::{self, Client};
+use std::cmp;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::runtime::TaskExecutor;
 
 mod error;
+mod types;
 
-use self::error::Result;
+use self::error::{ErrorKind, Result};
+pub use self::types::{
+    BlockEvent, BlockEvents, BlockRange, BlocksOptions, DecodedBlock, DecodedExtrinsic,
+    ExtrinsicEra, NumberOrHash, RangeBlock,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Most blocks returned by one block range request.
+const MAX_BLOCKS_LIMIT: u32 = 1000;
+/// Encoded size cap of the blocks returned by one block range request, if none is requested.
+const DEFAULT_MAX_BYTES: u64 = 4 * 1024 * 1024;
 
 build_rpc_trait! {
     pub trait ChainApiExt<Hash, Header, Number, Extrinsic> {
+        type Metadata;
 
         #[rpc(name = "chainext_getBlockByNumber")]
         fn block_info(&self, Trailing<Number>) -> Result<Option<SignedBlock<Header, Extrinsic, Hash>>>;
+
+        /// Blocks from `from` to `to`, both included, at most `limit` of them, which must not be
+        /// zero. `None` if there is no block `from`.
+        #[rpc(name = "chainext_getBlocks")]
+        fn blocks(&self, Number, Number, u32, Trailing<BlocksOptions>) -> Result<Option<BlockRange<Hash, Header, Extrinsic, Number>>>;
+
+        /// Extrinsics an account sent or received, the latest first, if the node indexes them.
+        #[rpc(name = "chainext_accountHistory")]
+        fn account_history(&self, AccountId, Trailing<u32>) -> Result<Vec<HistoryEntry>>;
+
+        #[rpc(name = "chainext_getDecodedBlock")]
+        fn decoded_block(&self, Trailing<NumberOrHash<Number, Hash>>) -> Result<Option<DecodedBlock<Hash, Header>>>;
+
+        /// Decoded events of a block, only those of the given modules if any are given.
+        #[rpc(name = "chainext_getEvents")]
+        fn events(&self, NumberOrHash<Number, Hash>, Trailing<Vec<String>>) -> Result<Option<Vec<BlockEvent>>>;
+
+        #[pubsub(name = "chainext_events")] {
+            /// Decoded events of each new best block, only those of the given modules if any
+            /// are given.
+            #[rpc(name = "chainext_subscribeEvents")]
+            fn subscribe_events(&self, Self::Metadata, pubsub::Subscriber<BlockEvents<Hash>>, Trailing<Vec<String>>);
+
+            /// Unsubscribe from the events subscription.
+            #[rpc(name = "chainext_unsubscribeEvents")]
+            fn unsubscribe_events(&self, SubscriptionId) -> RpcResult<bool>;
+        }
+    }
+}
+
+/// Outer and inner variant names of a runtime call or event with the inner content, which is
+/// the module name, call or event name and arguments.
+pub(crate) fn split_variants<T: Serialize>(
+    value: &T,
+    what: &'static str,
+) -> Result<(String, String, Value)> {
+    // the only entry of an externally tagged enum, `{ variant: content }`
+    fn variant(value: Value) -> Option<(String, Value)> {
+        match value {
+            Value::Object(map) => map.into_iter().next(),
+            // a variant without arguments
+            Value::String(name) => Some((name, Value::Null)),
+            _ => None,
+        }
+    }
+
+    let value = serde_json::to_value(value).map_err(|_| ErrorKind::Undecodable(what))?;
+    // runtime calls and events are `{ module: { name: args } }`
+    let (module, inner) = variant(value).ok_or(ErrorKind::Undecodable(what))?;
+    let (name, args) = variant(inner).ok_or(ErrorKind::Undecodable(what))?;
+    Ok((module, name, args))
+}
+
+/// System event records of the block `at`.
+fn event_records<B, E, Block>(
+    client: &Client<B, E, Block>,
+    at: &BlockId<Block>,
+) -> Result<Vec<EventRecord<Event>>>
+where
+    Block: BlockT + 'static,
+    B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+    E: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
+{
+    let key = StorageKey(twox_128(b"System Events").to_vec());
+    match client.storage(at, &key)? {
+        Some(data) => Ok(Decode::decode(&mut &data.0[..]).ok_or(ErrorKind::Undecodable("events"))?),
+        None => Ok(Vec::new()),
     }
 }
 
+/// Decoded events of the block `at`, only those of `modules` if given.
+fn block_events<B, E, Block>(
+    client: &Client<B, E, Block>,
+    at: &BlockId<Block>,
+    modules: &Option<Vec<String>>,
+) -> Result<Vec<BlockEvent>>
+where
+    Block: BlockT + 'static,
+    B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+    E: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
+{
+    let mut events = Vec::new();
+    for record in event_records(client, at)? {
+        let (module, event, args) = split_variants(&record.event, "event")?;
+        if let Some(ref modules) = modules {
+            if !modules.contains(&module) {
+                continue;
+            }
+        }
+        let extrinsic_index = match record.phase {
+            Phase::ApplyExtrinsic(i) => Some(i),
+            Phase::Finalization => None,
+        };
+        events.push(BlockEvent {
+            extrinsic_index,
+            module,
+            event,
+            args,
+        });
+    }
+    Ok(events)
+}
+
 pub struct ChainExt<B, E, Block: BlockT> {
     client: Arc<Client<B, E, Block>>,
+    subscriptions: Subscriptions,
+    account_history: Option<Arc<AccountHistory>>,
 }
 
 impl<B, E, Block: BlockT> ChainExt<B, E, Block> {
-    pub fn new(client: Arc<Client<B, E, Block>>, _executor: TaskExecutor) -> Self {
-        Self { client }
+    pub fn new(client: Arc<Client<B, E, Block>>, executor: TaskExecutor) -> Self {
+        Self {
+            client,
+            subscriptions: Subscriptions::new(executor),
+            account_history: None,
+        }
+    }
+
+    /// Serve the account history of an indexer.
+    pub fn with_account_history(mut self, account_history: Arc<AccountHistory>) -> Self {
+        self.account_history = Some(account_history);
+        self
+    }
+}
+
+impl<B, E, Block> ChainExt<B, E, Block>
+where
+    Block: BlockT + 'static,
+    B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
+    E: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
+{
+    /// Hash of the given block, `None` if there is no block of the given number.
+    fn block_hash(
+        &self,
+        block: NumberOrHash<NumberFor<Block>, Block::Hash>,
+    ) -> Result<Option<Block::Hash>> {
+        Ok(match block {
+            NumberOrHash::Hash(hash) => Some(hash),
+            NumberOrHash::Number(number) => self
+                .client
+                .header(&BlockId::number(number))?
+                .map(|header| header.hash()),
+        })
+    }
+
+    /// Whether each extrinsic of the block was applied, by the system events it left.
+    fn extrinsic_results(&self, at: &BlockId<Block>) -> Result<HashMap<u32, bool>> {
+        Ok(event_records(&self.client, at)?
+            .into_iter()
+            .filter_map(|record| match (record.phase, record.event) {
+                (Phase::ApplyExtrinsic(i), Event::system(system::Event::ExtrinsicSuccess)) => {
+                    Some((i, true))
+                }
+                (Phase::ApplyExtrinsic(i), Event::system(system::Event::ExtrinsicFailed)) => {
+                    Some((i, false))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Decode an opaque extrinsic of the block `at` with the runtime types.
+    fn decode_extrinsic(
+        &self,
+        at: &BlockId<Block>,
+        xt: &Block::Extrinsic,
+        success: Option<bool>,
+    ) -> Result<DecodedExtrinsic> {
+        // the opaque extrinsic encodes to the same bytes as the runtime one
+        let encoded = xt.encode();
+        let hash = BlakeTwo256::hash(&encoded);
+        let xt: UncheckedExtrinsic =
+            Decode::decode(&mut &encoded[..]).ok_or(ErrorKind::Undecodable("extrinsic"))?;
+
+        let (module, call, args) = split_variants(&xt.function, "call")?;
+        let (sender, index, era) = match xt.signature {
+            Some((address, _, index, era)) => {
+                let sender: Option<AccountId> =
+                    self.client.call_api_at(at, "lookup_address", &address)?;
+                (sender, Some(index), Some(ExtrinsicEra::from(era)))
+            }
+            None => (None, None, None),
+        };
+
+        Ok(DecodedExtrinsic {
+            hash,
+            sender,
+            index,
+            era,
+            module,
+            call,
+            args,
+            success,
+        })
     }
 }
 
@@ -36,22 +252,181 @@ where
     B: client::backend::Backend<Block, Blake2Hasher> + Send + Sync + 'static,
     E: client::CallExecutor<Block, Blake2Hasher> + Send + Sync + 'static,
 {
+    type Metadata = Metadata;
+
     fn block_info(
         &self,
         number: Trailing<NumberFor<Block>>,
     ) -> Result<Option<SignedBlock<Block::Header, Block::Extrinsic, Block::Hash>>> {
         let hash = match number.into() {
-            None => Some(self.client.info()?.chain.best_hash),
-            Some(number) => self
-                .client
-                .header(&BlockId::number(number))?
-                .map(|h| h.hash()),
+            None => self.client.info()?.chain.best_hash,
+            Some(number) => match self.block_hash(NumberOrHash::Number(number))? {
+                Some(hash) => hash,
+                None => return Ok(None),
+            },
+        };
+
+        Ok(self.client.block(&BlockId::Hash(hash))?)
+    }
+
+    fn blocks(
+        &self,
+        from: NumberFor<Block>,
+        to: NumberFor<Block>,
+        limit: u32,
+        options: Trailing<BlocksOptions>,
+    ) -> Result<Option<BlockRange<Block::Hash, Block::Header, Block::Extrinsic, NumberFor<Block>>>>
+    {
+        if from > to {
+            return Err(ErrorKind::InvalidRange.into());
+        }
+        // a page of no blocks would point `next` back at `from`
+        if limit == 0 {
+            return Err(ErrorKind::ZeroLimit.into());
+        }
+        let options: BlocksOptions = options.into().unwrap_or_default();
+        let limit = cmp::min(limit, MAX_BLOCKS_LIMIT) as usize;
+        let max_bytes = options.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+
+        let mut blocks = Vec::new();
+        let mut bytes = 0u64;
+        let mut number = from;
+        let next = loop {
+            if number > to {
+                break None;
+            }
+            if blocks.len() >= limit {
+                break Some(number);
+            }
+            let at = BlockId::number(number);
+            let header = match self.client.header(&at)? {
+                Some(header) => header,
+                None if blocks.is_empty() => return Ok(None),
+                // the chain ends inside the range
+                None => break None,
+            };
+            let extrinsics = if options.headers_only {
+                None
+            } else {
+                Some(self.client.body(&at)?.unwrap_or_default())
+            };
+
+            let size = (header.encode().len()
+                + extrinsics.as_ref().map_or(0, |xts| xts.encode().len()))
+                as u64;
+            if !blocks.is_empty() && bytes + size > max_bytes {
+                break Some(number);
+            }
+            bytes += size;
+            blocks.push(RangeBlock {
+                hash: header.hash(),
+                header,
+                extrinsics,
+            });
+            number = number + One::one();
         };
-        let block_hash = match hash {
+
+        Ok(Some(BlockRange { blocks, next }))
+    }
+
+    fn account_history(
+        &self,
+        account: AccountId,
+        page: Trailing<u32>,
+    ) -> Result<Vec<HistoryEntry>> {
+        let page: Option<u32> = page.into();
+        match self.account_history {
+            Some(ref history) => Ok(history.history(&account, page.unwrap_or(0))?),
+            None => Err(ErrorKind::IndexerDisabled.into()),
+        }
+    }
+
+    fn decoded_block(
+        &self,
+        block: Trailing<NumberOrHash<NumberFor<Block>, Block::Hash>>,
+    ) -> Result<Option<DecodedBlock<Block::Hash, Block::Header>>> {
+        let hash = match block.into() {
             None => self.client.info()?.chain.best_hash,
-            Some(h) => h,
+            Some(block) => match self.block_hash(block)? {
+                Some(hash) => hash,
+                None => return Ok(None),
+            },
+        };
+        let at = BlockId::Hash(hash);
+        let (header, extrinsics) = match self.client.block(&at)? {
+            Some(signed) => signed.block.deconstruct(),
+            None => return Ok(None),
+        };
+
+        let results = self.extrinsic_results(&at)?;
+        let extrinsics = extrinsics
+            .iter()
+            .enumerate()
+            .map(|(i, xt)| self.decode_extrinsic(&at, xt, results.get(&(i as u32)).cloned()))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(DecodedBlock {
+            hash,
+            header,
+            extrinsics,
+        }))
+    }
+
+    fn events(
+        &self,
+        block: NumberOrHash<NumberFor<Block>, Block::Hash>,
+        modules: Trailing<Vec<String>>,
+    ) -> Result<Option<Vec<BlockEvent>>> {
+        let hash = match self.block_hash(block)? {
+            Some(hash) => hash,
+            None => return Ok(None),
         };
+        let at = BlockId::Hash(hash);
+        if self.client.header(&at)?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(block_events(&self.client, &at, &modules.into())?))
+    }
+
+    fn subscribe_events(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: pubsub::Subscriber<BlockEvents<Block::Hash>>,
+        modules: Trailing<Vec<String>>,
+    ) {
+        let modules: Option<Vec<String>> = modules.into();
+        let client = self.client.clone();
+        self.subscriptions.add(subscriber, |sink| {
+            let stream = self
+                .client
+                .import_notification_stream()
+                .filter(|notification| notification.is_new_best)
+                .filter_map(move |notification| {
+                    let at = BlockId::Hash(notification.hash);
+                    match block_events(&client, &at, &modules) {
+                        Ok(events) => Some(BlockEvents {
+                            hash: notification.hash,
+                            events,
+                        }),
+                        Err(e) => {
+                            warn!(
+                                "Unable to decode events of {:?}: {:?}",
+                                notification.hash, e
+                            );
+                            None
+                        }
+                    }
+                })
+                .map(|events| Ok(events))
+                .map_err(|e| warn!("Block notification stream error: {:?}", e));
+            sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+                .send_all(stream)
+                // we ignore the resulting Stream (if the first stream is over we are unsubscribed)
+                .map(|_| ())
+        })
+    }
 
-        Ok(self.client.block(&BlockId::Hash(block_hash))?)
+    fn unsubscribe_events(&self, id: SubscriptionId) -> RpcResult<bool> {
+        Ok(self.subscriptions.cancel(id))
     }
 }
@@ -0,0 +1,228 @@
+// Copyright 2018 Akropolis.
+
+use super::*;
+use client::BlockOrigin;
+use test_client::runtime::{Block, Extrinsic, Hash, Header};
+use test_client::{self, TestClient};
+use tokio::runtime::Runtime;
+
+/// Chain extension API over an in-memory chain of `blocks` empty blocks after the genesis.
+fn chain_ext(
+    core: &Runtime,
+    blocks: u64,
+) -> ChainExt<test_client::Backend, test_client::Executor, Block> {
+    let client = Arc::new(test_client::new());
+    for _ in 0..blocks {
+        let block = client.new_block().unwrap().bake().unwrap();
+        client.justify_and_import(BlockOrigin::Own, block).unwrap();
+    }
+    ChainExt::new(client, core.executor())
+}
+
+fn numbers(range: &BlockRange<Hash, Header, Extrinsic, u64>) -> Vec<u64> {
+    range
+        .blocks
+        .iter()
+        .map(|block| block.header.number)
+        .collect()
+}
+
+#[test]
+fn block_info_should_not_fall_back_to_best_block() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    assert_eq!(
+        api.block_info(Some(1).into())
+            .unwrap()
+            .unwrap()
+            .block
+            .header
+            .number,
+        1
+    );
+    assert_eq!(
+        api.block_info(None.into())
+            .unwrap()
+            .unwrap()
+            .block
+            .header
+            .number,
+        2
+    );
+    assert!(api.block_info(Some(3).into()).unwrap().is_none());
+}
+
+#[test]
+fn blocks_should_return_range() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 5);
+
+    let range = api.blocks(1, 3, 10, None.into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![1, 2, 3]);
+    assert_eq!(range.next, None);
+    assert!(range.blocks.iter().all(|block| block.extrinsics.is_some()));
+
+    // the chain ends inside the range
+    let range = api.blocks(4, 10, 10, None.into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![4, 5]);
+    assert_eq!(range.next, None);
+}
+
+#[test]
+fn blocks_should_be_paginated() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 5);
+
+    let range = api.blocks(0, 5, 2, None.into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![0, 1]);
+    assert_eq!(range.next, Some(2));
+
+    let range = api.blocks(2, 5, 2, None.into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![2, 3]);
+    assert_eq!(range.next, Some(4));
+
+    let range = api.blocks(4, 5, 2, None.into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![4, 5]);
+    assert_eq!(range.next, None);
+}
+
+#[test]
+fn blocks_should_report_missing_start() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    assert!(api.blocks(3, 5, 10, None.into()).unwrap().is_none());
+    assert!(api.blocks(2, 1, 10, None.into()).is_err());
+}
+
+#[test]
+fn blocks_should_reject_zero_limit() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    let err = api.blocks(1, 2, 0, None.into()).err().unwrap();
+    match *err.kind() {
+        ErrorKind::ZeroLimit => {}
+        ref e => panic!("expected a zero limit error, got {:?}", e),
+    }
+}
+
+#[test]
+fn blocks_should_leave_out_extrinsics() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    let options = BlocksOptions {
+        headers_only: true,
+        max_bytes: None,
+    };
+    let range = api.blocks(1, 2, 10, Some(options).into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![1, 2]);
+    assert!(range.blocks.iter().all(|block| block.extrinsics.is_none()));
+}
+
+#[test]
+fn blocks_should_be_capped_by_size() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 3);
+
+    // a single block is returned even if it is larger than the cap
+    let options = BlocksOptions {
+        headers_only: true,
+        max_bytes: Some(1),
+    };
+    let range = api.blocks(1, 3, 10, Some(options).into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![1]);
+    assert_eq!(range.next, Some(2));
+
+    let header_size = range.blocks[0].header.encode().len() as u64;
+    let options = BlocksOptions {
+        headers_only: true,
+        max_bytes: Some(header_size * 2),
+    };
+    let range = api.blocks(1, 3, 10, Some(options).into()).unwrap().unwrap();
+    assert_eq!(numbers(&range), vec![1, 2]);
+    assert_eq!(range.next, Some(3));
+}
+
+#[test]
+fn decoded_block_should_find_block_by_number_or_hash() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    let block = api
+        .decoded_block(Some(NumberOrHash::Number(1)).into())
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.header.number, 1);
+    assert_eq!(block.hash, block.header.hash());
+    assert!(block.extrinsics.is_empty());
+
+    let by_hash = api
+        .decoded_block(Some(NumberOrHash::Hash(block.hash)).into())
+        .unwrap()
+        .unwrap();
+    assert_eq!(by_hash.header, block.header);
+
+    // the best block if none is given
+    let best = api.decoded_block(None.into()).unwrap().unwrap();
+    assert_eq!(best.header.number, 2);
+}
+
+#[test]
+fn decoded_block_should_report_missing_block() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    assert!(api
+        .decoded_block(Some(NumberOrHash::Number(3)).into())
+        .unwrap()
+        .is_none());
+    assert!(api
+        .decoded_block(Some(NumberOrHash::Hash([9; 32].into())).into())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn events_should_be_returned_for_known_blocks_only() {
+    let core = Runtime::new().unwrap();
+    let api = chain_ext(&core, 2);
+
+    // the test runtime deposits no events
+    assert_eq!(
+        api.events(NumberOrHash::Number(1), None.into())
+            .unwrap()
+            .unwrap()
+            .len(),
+        0
+    );
+    let hash = api
+        .decoded_block(Some(NumberOrHash::Number(2)).into())
+        .unwrap()
+        .unwrap()
+        .hash;
+    assert!(api
+        .events(NumberOrHash::Hash(hash), Some(vec!["system".into()]).into())
+        .unwrap()
+        .is_some());
+
+    assert!(api
+        .events(NumberOrHash::Number(3), None.into())
+        .unwrap()
+        .is_none());
+    assert!(api
+        .events(NumberOrHash::Hash([9; 32].into()), None.into())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn event_should_split_into_module_name_and_args() {
+    let event = Event::system(system::Event::ExtrinsicSuccess);
+    let (module, name, args) = split_variants(&event, "event").unwrap();
+    assert_eq!(module, "system");
+    assert_eq!(name, "ExtrinsicSuccess");
+    assert_eq!(args, Value::Null);
+}
@@ -0,0 +1,108 @@
+// Copyright 2018 Akropolis.
+
+//! Types returned by the chain extension RPC.
+
+use akro_primitives::{AccountId, Hash, Index};
+use runtime_primitives::generic::Era;
+use serde_json::Value;
+
+/// A block given by its number or by its hash.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum NumberOrHash<Number, Hash> {
+    Number(Number),
+    Hash(Hash),
+}
+
+/// Options of a block range request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BlocksOptions {
+    /// Leave out the extrinsics of the blocks.
+    pub headers_only: bool,
+    /// Cap of the encoded size of the returned blocks, at least one block is always returned.
+    pub max_bytes: Option<u64>,
+}
+
+/// A block of a block range, without extrinsics if only headers were requested.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeBlock<Hash, Header, Extrinsic> {
+    pub hash: Hash,
+    pub header: Header,
+    pub extrinsics: Option<Vec<Extrinsic>>,
+}
+
+/// Consecutive blocks of a block range.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockRange<Hash, Header, Extrinsic, Number> {
+    /// Blocks in ascending order from the start of the range.
+    pub blocks: Vec<RangeBlock<Hash, Header, Extrinsic>>,
+    /// Number to request the rest of the range from, `None` if the range is complete or the
+    /// chain ends before it.
+    pub next: Option<Number>,
+}
+
+/// A block with its extrinsics decoded by the runtime.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedBlock<Hash, Header> {
+    pub hash: Hash,
+    pub header: Header,
+    pub extrinsics: Vec<DecodedExtrinsic>,
+}
+
+/// An extrinsic decoded by the runtime.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedExtrinsic {
+    pub hash: Hash,
+    /// Signer of the extrinsic, `None` for inherents or an unknown account index.
+    pub sender: Option<AccountId>,
+    pub index: Option<Index>,
+    pub era: Option<ExtrinsicEra>,
+    pub module: String,
+    pub call: String,
+    /// Arguments as the runtime serializes them, the value itself for a single argument and
+    /// an array for several.
+    pub args: Value,
+    /// Whether the extrinsic was applied, `None` if the block left no result for it.
+    pub success: Option<bool>,
+}
+
+/// A runtime event of a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockEvent {
+    /// Index of the extrinsic which deposited the event, `None` for finalisation.
+    pub extrinsic_index: Option<u32>,
+    pub module: String,
+    pub event: String,
+    pub args: Value,
+}
+
+/// Events of a new best block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockEvents<Hash> {
+    pub hash: Hash,
+    pub events: Vec<BlockEvent>,
+}
+
+/// Period in which a signed extrinsic is valid.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtrinsicEra {
+    Immortal,
+    Mortal { period: u64, phase: u64 },
+}
+
+impl From<Era> for ExtrinsicEra {
+    fn from(era: Era) -> Self {
+        match era {
+            Era::Immortal => ExtrinsicEra::Immortal,
+            Era::Mortal(period, phase) => ExtrinsicEra::Mortal { period, phase },
+        }
+    }
+}
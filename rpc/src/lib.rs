@@ -5,17 +5,29 @@ extern crate substrate_rpc as rpc;
 extern crate log;
 
 extern crate akro_api;
+extern crate akro_indexer;
+extern crate akro_pool;
+extern crate akro_primitives;
+extern crate akro_runtime;
+extern crate futures;
 extern crate jsonrpc_core;
 extern crate jsonrpc_http_server as http;
 extern crate jsonrpc_pubsub as pubsub;
 extern crate jsonrpc_ws_server as ws;
+extern crate parity_codec as codec;
 extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
 extern crate sr_primitives as runtime_primitives;
+extern crate srml_system;
 extern crate substrate_client as client;
 extern crate substrate_primitives as primitives;
 extern crate substrate_rpc;
 pub extern crate substrate_rpc as apis;
 extern crate substrate_rpc_servers as rpc_server;
+#[cfg(test)]
+extern crate substrate_test_client as test_client;
 extern crate tokio;
 #[macro_use]
 extern crate error_chain;
@@ -23,8 +35,13 @@ extern crate error_chain;
 #[macro_use]
 extern crate jsonrpc_macros;
 
+pub mod account;
 pub mod chainext;
 pub mod servers;
+pub mod staking;
+pub mod tokenstaking;
+pub mod txpool;
+mod views;
 
 use std::io;
 use std::net::SocketAddr;
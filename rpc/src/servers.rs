@@ -10,7 +10,11 @@ use std;
 use std::io;
 use ws;
 
+use account;
 use chainext;
+use staking;
+use tokenstaking;
+use txpool;
 
 type Metadata = apis::metadata::Metadata;
 type RpcHandler = pubsub::PubSubHandler<Metadata>;
@@ -18,10 +22,14 @@ pub type HttpServer = http::Server;
 pub type WsServer = ws::Server;
 
 /// Construct rpc `IoHandler`
-pub fn rpc_handler<Block: BlockT, ExHash, PendingExtrinsics, S, C, CE, A, Y>(
+pub fn rpc_handler<Block: BlockT, ExHash, PendingExtrinsics, S, C, CE, ST, TS, AC, TP, A, Y>(
     state: S,
     chain: C,
     chainext: CE,
+    staking: ST,
+    tokenstaking: TS,
+    account: AC,
+    txpool: TP,
     author: A,
     system: Y,
 ) -> RpcHandler
@@ -41,7 +49,17 @@ where
         Block::Extrinsic,
         Metadata = Metadata,
     >,
-    CE: chainext::ChainApiExt<Block::Hash, Block::Header, NumberFor<Block>, Block::Extrinsic>,
+    CE: chainext::ChainApiExt<
+        Block::Hash,
+        Block::Header,
+        NumberFor<Block>,
+        Block::Extrinsic,
+        Metadata = Metadata,
+    >,
+    ST: staking::StakingApi,
+    TS: tokenstaking::TokenStakingApi,
+    AC: account::AccountApi,
+    TP: txpool::TxPoolApi<Metadata = Metadata>,
     A: apis::author::AuthorApi<
         ExHash,
         Block::Hash,
@@ -55,6 +73,10 @@ where
     io.extend_with(state.to_delegate());
     io.extend_with(chain.to_delegate());
     io.extend_with(chainext.to_delegate());
+    io.extend_with(staking.to_delegate());
+    io.extend_with(tokenstaking.to_delegate());
+    io.extend_with(account.to_delegate());
+    io.extend_with(txpool.to_delegate());
     io.extend_with(author.to_delegate());
     io.extend_with(system.to_delegate());
     io
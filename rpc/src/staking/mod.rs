@@ -0,0 +1,131 @@
+// Copyright 2018 Akropolis.
+
+//! Staking RPC, decoded views of the staking module.
+
+use akro_api::AkroApi;
+use akro_primitives::{AccountId, Balance, Hash};
+use jsonrpc_macros::Trailing;
+use std::sync::Arc;
+use views::{self, Result};
+
+mod types;
+
+pub use self::types::{
+    AuthoredBlocks, CertInfo, EraInfo, IntentionInfo, NominationRecordInfo, UnbondingInfo,
+};
+
+build_rpc_trait! {
+    pub trait StakingApi {
+        #[rpc(name = "staking_intentions")]
+        fn intentions(&self, Trailing<Hash>) -> Result<Vec<IntentionInfo>>;
+
+        #[rpc(name = "staking_intention")]
+        fn intention(&self, AccountId, Trailing<Hash>) -> Result<Option<IntentionInfo>>;
+
+        #[rpc(name = "staking_nominationRecords")]
+        fn nomination_records(&self, AccountId, Trailing<Hash>) -> Result<Vec<NominationRecordInfo>>;
+
+        #[rpc(name = "staking_pendingDividend")]
+        fn pending_dividend(&self, AccountId, AccountId, Trailing<Hash>) -> Result<Balance>;
+
+        #[rpc(name = "staking_certs")]
+        fn certs(&self, Trailing<Hash>) -> Result<Vec<CertInfo>>;
+
+        #[rpc(name = "staking_unbonding")]
+        fn unbonding(&self, AccountId, Trailing<Hash>) -> Result<Vec<UnbondingInfo>>;
+
+        #[rpc(name = "staking_eraInfo")]
+        fn era_info(&self, Trailing<Hash>) -> Result<EraInfo>;
+
+        #[rpc(name = "staking_authoredBlocks")]
+        fn authored_blocks(&self, AccountId, Trailing<Hash>) -> Result<AuthoredBlocks>;
+    }
+}
+
+pub struct Staking<A> {
+    api: Arc<A>,
+}
+
+impl<A: AkroApi> Staking<A> {
+    pub fn new(api: Arc<A>) -> Self {
+        Self { api }
+    }
+}
+
+impl<A> StakingApi for Staking<A>
+where
+    A: AkroApi + Send + Sync + 'static,
+{
+    fn intentions(&self, hash: Trailing<Hash>) -> Result<Vec<IntentionInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .intentions(&at)?
+            .into_iter()
+            .map(|(account, iprof)| IntentionInfo::new(account, iprof))
+            .collect())
+    }
+
+    fn intention(&self, account: AccountId, hash: Trailing<Hash>) -> Result<Option<IntentionInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .intention(&at, account)?
+            .map(|iprof| IntentionInfo::new(account, iprof)))
+    }
+
+    fn nomination_records(
+        &self,
+        nominator: AccountId,
+        hash: Trailing<Hash>,
+    ) -> Result<Vec<NominationRecordInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .nomination_records(&at, nominator)?
+            .into_iter()
+            .map(|(intention, record, dividend)| {
+                NominationRecordInfo::new(intention, record, dividend)
+            })
+            .collect())
+    }
+
+    fn pending_dividend(
+        &self,
+        nominator: AccountId,
+        intention: AccountId,
+        hash: Trailing<Hash>,
+    ) -> Result<Balance> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self.api.pending_dividend(&at, nominator, intention)?)
+    }
+
+    fn certs(&self, hash: Trailing<Hash>) -> Result<Vec<CertInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self.api.certs(&at)?.into_iter().map(Into::into).collect())
+    }
+
+    fn unbonding(&self, account: AccountId, hash: Trailing<Hash>) -> Result<Vec<UnbondingInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .unbonding(&at, account)?
+            .into_iter()
+            .map(|(unlock_at, value)| UnbondingInfo { unlock_at, value })
+            .collect())
+    }
+
+    fn era_info(&self, hash: Trailing<Hash>) -> Result<EraInfo> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self.api.era_info(&at)?.into())
+    }
+
+    fn authored_blocks(&self, account: AccountId, hash: Trailing<Hash>) -> Result<AuthoredBlocks> {
+        let at = views::block_id(&*self.api, hash)?;
+        let (current_era, last_era) = self.api.authored_blocks(&at, account)?;
+        Ok(AuthoredBlocks {
+            current_era,
+            last_era,
+        })
+    }
+}
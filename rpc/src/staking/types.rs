@@ -0,0 +1,130 @@
+// Copyright 2018 Akropolis.
+
+//! Types returned by the staking RPC.
+
+use akro_api::{CertProfs, EraInfo as RuntimeEraInfo, IntentionProfs, NominationRecord};
+use akro_primitives::{AccountId, Balance, BlockNumber};
+
+fn to_string(bytes: Vec<u8>) -> String {
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// An intention with its profile.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntentionInfo {
+    pub account: AccountId,
+    pub name: String,
+    pub url: String,
+    pub is_active: bool,
+    pub frozen: Balance,
+    pub jackpot: Balance,
+    pub total_nomination: Balance,
+    pub last_total_vote_weight: u64,
+    pub last_total_vote_weight_update: BlockNumber,
+}
+
+impl IntentionInfo {
+    pub fn new(account: AccountId, iprof: IntentionProfs) -> Self {
+        IntentionInfo {
+            account,
+            name: to_string(iprof.name),
+            url: to_string(iprof.url),
+            is_active: iprof.is_active,
+            frozen: iprof.frozen,
+            jackpot: iprof.jackpot,
+            total_nomination: iprof.total_nomination,
+            last_total_vote_weight: iprof.last_total_vote_weight,
+            last_total_vote_weight_update: iprof.last_total_vote_weight_update,
+        }
+    }
+}
+
+/// A nomination of a nominator, with the dividend a claim would pay now.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NominationRecordInfo {
+    pub intention: AccountId,
+    pub nomination: Balance,
+    pub last_vote_weight: u64,
+    pub last_vote_weight_update: BlockNumber,
+    pub pending_dividend: Balance,
+}
+
+impl NominationRecordInfo {
+    pub fn new(intention: AccountId, record: NominationRecord, pending_dividend: Balance) -> Self {
+        NominationRecordInfo {
+            intention,
+            nomination: record.nomination,
+            last_vote_weight: record.last_vote_weight,
+            last_vote_weight_update: record.last_vote_weight_update,
+            pending_dividend,
+        }
+    }
+}
+
+/// An issued certificate.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertInfo {
+    pub name: String,
+    pub index: u32,
+    pub owner: AccountId,
+    pub issued_on: BlockNumber,
+    pub frozen_duration: u32,
+    pub remaining_shares: u32,
+}
+
+impl From<CertProfs> for CertInfo {
+    fn from(cert: CertProfs) -> Self {
+        CertInfo {
+            name: to_string(cert.name),
+            index: cert.index,
+            owner: cert.owner,
+            issued_on: cert.issued_on,
+            frozen_duration: cert.frozen_duration,
+            remaining_shares: cert.remaining_shares,
+        }
+    }
+}
+
+/// Stake waiting to be unreserved.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnbondingInfo {
+    pub unlock_at: BlockNumber,
+    pub value: Balance,
+}
+
+/// Blocks a validator authored in the current and the previous era.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoredBlocks {
+    pub current_era: u32,
+    pub last_era: u32,
+}
+
+/// Era and session progress.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EraInfo {
+    pub current_era: BlockNumber,
+    pub sessions_per_era: BlockNumber,
+    pub era_length: BlockNumber,
+    pub last_era_length_change: BlockNumber,
+    pub current_session: BlockNumber,
+    pub session_length: BlockNumber,
+}
+
+impl From<RuntimeEraInfo> for EraInfo {
+    fn from(era: RuntimeEraInfo) -> Self {
+        EraInfo {
+            current_era: era.current_era,
+            sessions_per_era: era.sessions_per_era,
+            era_length: era.era_length,
+            last_era_length_change: era.last_era_length_change,
+            current_session: era.current_session,
+            session_length: era.session_length,
+        }
+    }
+}
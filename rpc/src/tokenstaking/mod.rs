@@ -0,0 +1,77 @@
+// Copyright 2018 Akropolis.
+
+//! Token-staking RPC, decoded views of the token-staking module.
+
+use akro_api::AkroApi;
+use akro_primitives::{AccountId, Hash};
+use jsonrpc_macros::Trailing;
+use std::sync::Arc;
+use views::{self, Result};
+
+mod types;
+
+pub use self::types::{HodlingRecordInfo, SessionStakeInfo, StakingTokenInfo};
+
+build_rpc_trait! {
+    pub trait TokenStakingApi {
+        #[rpc(name = "tokenstaking_tokens")]
+        fn tokens(&self, Trailing<Hash>) -> Result<Vec<StakingTokenInfo>>;
+
+        #[rpc(name = "tokenstaking_hodlingRecords")]
+        fn hodling_records(&self, AccountId, Trailing<Hash>) -> Result<Vec<HodlingRecordInfo>>;
+
+        #[rpc(name = "tokenstaking_sessionStakes")]
+        fn session_stakes(&self, Trailing<Hash>) -> Result<Vec<SessionStakeInfo>>;
+    }
+}
+
+pub struct TokenStaking<A> {
+    api: Arc<A>,
+}
+
+impl<A: AkroApi> TokenStaking<A> {
+    pub fn new(api: Arc<A>) -> Self {
+        Self { api }
+    }
+}
+
+impl<A> TokenStakingApi for TokenStaking<A>
+where
+    A: AkroApi + Send + Sync + 'static,
+{
+    fn tokens(&self, hash: Trailing<Hash>) -> Result<Vec<StakingTokenInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .staking_tokens(&at)?
+            .into_iter()
+            .map(|(sym, profs, total_weight)| StakingTokenInfo::new(sym, profs, total_weight))
+            .collect())
+    }
+
+    fn hodling_records(
+        &self,
+        account: AccountId,
+        hash: Trailing<Hash>,
+    ) -> Result<Vec<HodlingRecordInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .hodling_records(&at, account)?
+            .into_iter()
+            .map(|(sym, record, weight, claimable)| {
+                HodlingRecordInfo::new(sym, record, weight, claimable)
+            })
+            .collect())
+    }
+
+    fn session_stakes(&self, hash: Trailing<Hash>) -> Result<Vec<SessionStakeInfo>> {
+        let at = views::block_id(&*self.api, hash)?;
+        Ok(self
+            .api
+            .session_stakes(&at)?
+            .into_iter()
+            .map(|(sym, stake)| SessionStakeInfo::new(sym, stake))
+            .collect())
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2018 Akropolis.
+
+//! Types returned by the token-staking RPC.
+
+use akro_api::{HodlingRecord, SessionStake, Symbol, VirtualIntentionProfs};
+use akro_primitives::{Balance, BlockNumber};
+
+fn to_string(sym: Symbol) -> String {
+    String::from_utf8_lossy(&sym).into_owned()
+}
+
+/// A token taking part in token staking, with the jackpot of its virtual intention.
+///
+/// Weights are u128 in the runtime and given as strings, which JSON numbers can not hold.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingTokenInfo {
+    pub symbol: String,
+    pub jackpot: Balance,
+    pub total_weight: String,
+    pub last_total_weight_update: BlockNumber,
+}
+
+impl StakingTokenInfo {
+    pub fn new(sym: Symbol, profs: VirtualIntentionProfs, total_weight: u128) -> Self {
+        StakingTokenInfo {
+            symbol: to_string(sym),
+            jackpot: profs.jackpot,
+            total_weight: total_weight.to_string(),
+            last_total_weight_update: profs.last_total_weight_update,
+        }
+    }
+}
+
+/// A hodling record of an account, with the dividend a claim would pay now.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HodlingRecordInfo {
+    pub symbol: String,
+    pub weight: String,
+    pub last_weight_update: BlockNumber,
+    pub immature_amount: String,
+    /// Block at which the next deposit still warming up matures.
+    pub mature_at: Option<BlockNumber>,
+    pub claimable: Balance,
+}
+
+impl HodlingRecordInfo {
+    pub fn new(sym: Symbol, record: HodlingRecord, weight: u128, claimable: Balance) -> Self {
+        HodlingRecordInfo {
+            symbol: to_string(sym),
+            weight: weight.to_string(),
+            last_weight_update: record.last_weight_update,
+            immature_amount: record.immature_amount().to_string(),
+            mature_at: record.next_maturity(),
+            claimable,
+        }
+    }
+}
+
+/// Price and discounted stake a token was given its session reward share by.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStakeInfo {
+    pub symbol: String,
+    pub price: u64,
+    pub discounted_stake: Balance,
+    pub updated_at: BlockNumber,
+}
+
+impl SessionStakeInfo {
+    pub fn new(sym: Symbol, stake: SessionStake) -> Self {
+        SessionStakeInfo {
+            symbol: to_string(sym),
+            price: stake.price,
+            discounted_stake: stake.discounted_stake,
+            updated_at: stake.updated_at,
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use akro_pool;
+use jsonrpc_core as rpccore;
+
+error_chain! {
+    links {
+        Pool(akro_pool::Error, akro_pool::ErrorKind) #[doc = "Pool error"];
+    }
+    errors {
+        /// The best block is not known
+        UnknownBlock {
+            description("unknown block"),
+            display("Unknown block"),
+        }
+        /// A pooled extrinsic the runtime could not decode
+        Undecodable(what: &'static str) {
+            description("undecodable data"),
+            display("Undecodable {}", what),
+        }
+        /// An unsafe method called on a server listening on external interfaces
+        UnsafeRpcCalled {
+            description("unsafe method called"),
+            display("Unsafe method is only allowed on a server listening locally"),
+        }
+    }
+}
+
+impl From<Error> for rpccore::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error(ErrorKind::UnknownBlock, _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Unknown block".into(),
+                data: None,
+            },
+            Error(ErrorKind::UnsafeRpcCalled, _) => rpccore::Error {
+                code: rpccore::ErrorCode::MethodNotFound,
+                message: "Method not found".into(),
+                data: Some("unsafe method is not allowed on an external interface".into()),
+            },
+            e => {
+                warn!("Unknown error: {:?}", e);
+                rpccore::Error {
+                    code: rpccore::ErrorCode::InternalError,
+                    message: "Unknown error occured".into(),
+                    data: Some(format!("{:?}", e).into()),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright 2018 Akropolis.
+
+//! Transaction pool RPC, inspection and management of the pending transactions.
+
+use akro_api::AkroApi;
+use akro_pool::watcher::Status;
+use akro_pool::{ExtrinsicFor, PoolApi, StatusStream, TransactionPool};
+use akro_primitives::{AccountId, Hash};
+use akro_runtime::UncheckedExtrinsic;
+use apis::metadata::Metadata;
+use apis::Subscriptions;
+use chainext::{split_variants, ExtrinsicEra};
+use codec::{Decode, Encode};
+use futures::{stream, Future, Sink, Stream};
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_macros::{pubsub, Trailing};
+use primitives::Bytes;
+use pubsub::SubscriptionId;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::runtime::TaskExecutor;
+
+mod error;
+mod types;
+
+use self::error::{ErrorKind, Result};
+pub use self::types::{PendingExtrinsic, PoolStatus, TransactionStatus};
+
+#[cfg(test)]
+mod tests;
+
+build_rpc_trait! {
+    pub trait TxPoolApi {
+        type Metadata;
+
+        /// Counts of the ready and future transactions, their memory usage and senders.
+        #[rpc(name = "pool_status")]
+        fn status(&self) -> Result<PoolStatus>;
+
+        /// Decoded ready transactions, only those of the given sender if given.
+        #[rpc(name = "pool_pending")]
+        fn pending(&self, Trailing<AccountId>) -> Result<Vec<PendingExtrinsic>>;
+
+        /// Remove a transaction from the pool, only allowed on a server listening locally.
+        #[rpc(name = "pool_remove")]
+        fn remove(&self, Hash) -> Result<bool>;
+
+        #[pubsub(name = "pool_extrinsicUpdate")] {
+            /// Submit an extrinsic and follow its status until it is included or dropped.
+            #[rpc(name = "pool_submitAndWatchExtrinsic")]
+            fn watch_extrinsic(&self, Self::Metadata, pubsub::Subscriber<TransactionStatus<Hash>>, Bytes);
+
+            /// Unsubscribe from the status of an extrinsic.
+            #[rpc(name = "pool_unwatchExtrinsic")]
+            fn unwatch_extrinsic(&self, SubscriptionId) -> RpcResult<bool>;
+        }
+
+        #[pubsub(name = "pool_extrinsicUpdate")] {
+            /// Follow the status of an extrinsic already in the pool, only allowed on a server
+            /// listening locally. Not supported by the pool, the subscriber gets the reason.
+            #[rpc(name = "pool_watchExtrinsic")]
+            fn watch_pooled_extrinsic(&self, Self::Metadata, pubsub::Subscriber<TransactionStatus<Hash>>, Hash);
+
+            /// Unsubscribe from the status of a pooled extrinsic.
+            #[rpc(name = "pool_unwatchPooledExtrinsic")]
+            fn unwatch_pooled_extrinsic(&self, SubscriptionId) -> RpcResult<bool>;
+        }
+    }
+}
+
+pub struct TxPool<A>
+where
+    A: AkroApi + Send + Sync,
+{
+    pool: Arc<TransactionPool<A>>,
+    subscriptions: Subscriptions,
+    allow_unsafe: bool,
+}
+
+impl<A> TxPool<A>
+where
+    A: AkroApi + Send + Sync,
+{
+    /// `allow_unsafe` enables the methods which change the pool, for servers listening locally.
+    pub fn new(pool: Arc<TransactionPool<A>>, executor: TaskExecutor, allow_unsafe: bool) -> Self {
+        Self {
+            pool,
+            subscriptions: Subscriptions::new(executor),
+            allow_unsafe,
+        }
+    }
+
+    /// Send the statuses of a watched extrinsic to the subscriber, or the reason it is invalid.
+    fn subscribe(
+        &self,
+        subscriber: pubsub::Subscriber<TransactionStatus<Hash>>,
+        submitted: ::std::result::Result<StatusStream, String>,
+    ) {
+        let statuses: Box<Stream<Item = TransactionStatus<Hash>, Error = ()> + Send> =
+            match submitted {
+                Ok(statuses) => Box::new(statuses.filter_map(|status| match status {
+                    Status::Future => Some(TransactionStatus::Future),
+                    Status::Ready => Some(TransactionStatus::Ready),
+                    Status::Finalised(hash) => Some(TransactionStatus::Included(hash)),
+                    Status::Usurped(_) | Status::Dropped => Some(TransactionStatus::Dropped),
+                    Status::Broadcast(_) => None,
+                })),
+                Err(reason) => {
+                    debug!(target: "transaction-pool", "Rejected watched extrinsic: {}", reason);
+                    Box::new(stream::once(Ok(TransactionStatus::Invalid(reason))))
+                }
+            };
+
+        self.subscriptions.add(subscriber, |sink| {
+            sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+                .send_all(statuses.map(|status| Ok(status)))
+                // we ignore the resulting Stream (if the first stream is over we are unsubscribed)
+                .map(|_| ())
+        })
+    }
+}
+
+/// Reject the methods which change the pool unless unsafe methods are allowed.
+fn ensure_unsafe_allowed(allow_unsafe: bool) -> Result<()> {
+    if allow_unsafe {
+        Ok(())
+    } else {
+        Err(ErrorKind::UnsafeRpcCalled.into())
+    }
+}
+
+/// Era, module, call and arguments of a pooled extrinsic, from its encoding.
+fn decode_pending(encoded: &[u8]) -> Result<(ExtrinsicEra, String, String, Value)> {
+    // the opaque extrinsic encodes to the same bytes as the runtime one
+    let xt: UncheckedExtrinsic =
+        Decode::decode(&mut &encoded[..]).ok_or(ErrorKind::Undecodable("extrinsic"))?;
+    let era = xt
+        .signature
+        .map(|(_, _, _, era)| era)
+        .ok_or(ErrorKind::Undecodable("extrinsic"))?;
+    let (module, call, args) =
+        split_variants(&xt.function, "call").map_err(|_| ErrorKind::Undecodable("call"))?;
+    Ok((era.into(), module, call, args))
+}
+
+impl<A> TxPoolApi for TxPool<A>
+where
+    A: AkroApi + Send + Sync + 'static,
+{
+    type Metadata = Metadata;
+
+    fn status(&self) -> Result<PoolStatus> {
+        let at = self.pool.best_block_id().ok_or(ErrorKind::UnknownBlock)?;
+        // reading the pending set leaves culling to the block imports
+        let ready = self.pool.inner().pending(&at, |pending| pending.count());
+        Ok(PoolStatus::new(ready, self.pool.inner().light_status()))
+    }
+
+    fn pending(&self, sender: Trailing<AccountId>) -> Result<Vec<PendingExtrinsic>> {
+        let sender: Option<AccountId> = sender.into();
+        let at = self.pool.best_block_id().ok_or(ErrorKind::UnknownBlock)?;
+        let pending = self.pool.inner().pending(&at, |pending| {
+            pending
+                .filter(|t| sender.map_or(true, |sender| t.verified.sender == sender))
+                .map(|t| (t.original.encode(), t.verified.clone()))
+                .collect::<Vec<_>>()
+        });
+
+        pending
+            .into_iter()
+            .map(|(encoded, verified)| {
+                let (era, module, call, args) = decode_pending(&encoded)?;
+                Ok(PendingExtrinsic {
+                    hash: verified.hash,
+                    sender: verified.sender,
+                    index: verified.index,
+                    era,
+                    module,
+                    call,
+                    args,
+                    fee: verified.fee(),
+                    encoded_size: verified.encoded_size(),
+                })
+            })
+            .collect()
+    }
+
+    fn remove(&self, hash: Hash) -> Result<bool> {
+        ensure_unsafe_allowed(self.allow_unsafe)?;
+        let removed = self.pool.inner().remove(&[hash], false);
+        Ok(removed.into_iter().any(|xt| xt.is_some()))
+    }
+
+    fn watch_extrinsic(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: pubsub::Subscriber<TransactionStatus<Hash>>,
+        xt: Bytes,
+    ) {
+        let submitted = self
+            .pool
+            .best_block_id()
+            .ok_or_else(|| "best block unknown".to_string())
+            .and_then(|at| {
+                let xt: ExtrinsicFor<PoolApi<A>> = Decode::decode(&mut &xt[..])
+                    .ok_or_else(|| "bad extrinsic format".to_string())?;
+                self.pool
+                    .submit_and_watch(&at, xt)
+                    .map_err(|e| e.to_string())
+            });
+        self.subscribe(subscriber, submitted)
+    }
+
+    fn unwatch_extrinsic(&self, id: SubscriptionId) -> RpcResult<bool> {
+        Ok(self.subscriptions.cancel(id))
+    }
+
+    fn watch_pooled_extrinsic(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: pubsub::Subscriber<TransactionStatus<Hash>>,
+        hash: Hash,
+    ) {
+        let watched = ensure_unsafe_allowed(self.allow_unsafe)
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                self.pool
+                    .best_block_id()
+                    .ok_or_else(|| "best block unknown".to_string())
+            })
+            .and_then(|at| self.pool.watch(&at, hash).map_err(|e| e.to_string()));
+        self.subscribe(subscriber, watched)
+    }
+
+    fn unwatch_pooled_extrinsic(&self, id: SubscriptionId) -> RpcResult<bool> {
+        Ok(self.subscriptions.cancel(id))
+    }
+}
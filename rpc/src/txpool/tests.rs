@@ -0,0 +1,99 @@
+// Copyright 2018 Akropolis.
+
+use super::*;
+use akro_runtime::{Call, ConsensusCall, RawAddress};
+use jsonrpc_core as rpccore;
+use primitives::ed25519::Pair;
+use runtime_primitives::generic::Era;
+
+fn extrinsic(signed: bool, era: Era) -> Vec<u8> {
+    let function = Call::Consensus(ConsensusCall::note_offline(vec![1]));
+    let signature = if signed {
+        let key = Pair::from_seed(b"Alice                           ");
+        let payload = (0u64, function.clone());
+        Some((
+            RawAddress::Id(key.public().0.into()),
+            key.sign(&payload.encode()).into(),
+            payload.0,
+            era,
+        ))
+    } else {
+        None
+    };
+    UncheckedExtrinsic {
+        signature,
+        function,
+    }
+    .encode()
+}
+
+#[test]
+fn pool_status_should_count_the_others_as_future() {
+    let status = PoolStatus::new(
+        2,
+        LightStatus {
+            mem_usage: 300,
+            transaction_count: 5,
+            senders: 2,
+        },
+    );
+    assert_eq!(status.ready, 2);
+    assert_eq!(status.future, 3);
+    assert_eq!(status.mem_usage, 300);
+    assert_eq!(status.senders, 2);
+
+    // transactions imported between reading the pending set and the counts
+    let status = PoolStatus::new(
+        3,
+        LightStatus {
+            mem_usage: 100,
+            transaction_count: 1,
+            senders: 1,
+        },
+    );
+    assert_eq!(status.future, 0);
+}
+
+#[test]
+fn pending_extrinsic_should_be_decoded() {
+    let (era, module, call, args) = decode_pending(&extrinsic(true, Era::immortal())).unwrap();
+    assert_eq!(era, ExtrinsicEra::Immortal);
+    assert_eq!(module, "Consensus");
+    assert_eq!(call, "note_offline");
+    assert_eq!(args, Value::from(vec![1]));
+
+    let (era, ..) = decode_pending(&extrinsic(true, Era::mortal(64, 10))).unwrap();
+    assert_eq!(
+        era,
+        ExtrinsicEra::Mortal {
+            period: 64,
+            phase: 10
+        }
+    );
+}
+
+#[test]
+fn pending_extrinsic_should_be_signed() {
+    for encoded in &[extrinsic(false, Era::immortal()), vec![1, 2, 3]] {
+        let err = decode_pending(encoded).err().unwrap();
+        match *err.kind() {
+            ErrorKind::Undecodable("extrinsic") => {}
+            _ => panic!("unexpected error {:?}", err),
+        }
+    }
+}
+
+#[test]
+fn pool_remove_should_be_unsafe() {
+    assert!(ensure_unsafe_allowed(true).is_ok());
+
+    let err = ensure_unsafe_allowed(false).err().unwrap();
+    match *err.kind() {
+        ErrorKind::UnsafeRpcCalled => {}
+        _ => panic!("unexpected error {:?}", err),
+    }
+    assert_eq!(
+        rpccore::Error::from(err).code,
+        rpccore::ErrorCode::MethodNotFound
+    );
+}
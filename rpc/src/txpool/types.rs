@@ -0,0 +1,66 @@
+// Copyright 2018 Akropolis.
+
+//! Types returned by the transaction pool RPC.
+
+use akro_pool::LightStatus;
+use akro_primitives::{AccountId, Balance, Hash, Index};
+use chainext::ExtrinsicEra;
+use serde_json::Value;
+
+/// Transactions and memory held by the pool.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus {
+    /// Transactions which can be included in the next block.
+    pub ready: usize,
+    /// Transactions waiting for a lower index of their sender.
+    pub future: usize,
+    /// Encoded size of all the transactions in bytes.
+    pub mem_usage: usize,
+    /// Distinct senders of the transactions.
+    pub senders: usize,
+}
+
+impl PoolStatus {
+    /// Status of a pool with `ready` ready transactions, the others are future ones.
+    pub fn new(ready: usize, status: LightStatus) -> Self {
+        PoolStatus {
+            ready,
+            future: status.transaction_count.saturating_sub(ready),
+            mem_usage: status.mem_usage,
+            senders: status.senders,
+        }
+    }
+}
+
+/// A ready transaction of the pool, decoded by the runtime.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingExtrinsic {
+    pub hash: Hash,
+    pub sender: AccountId,
+    pub index: Index,
+    pub era: ExtrinsicEra,
+    pub module: String,
+    pub call: String,
+    pub args: Value,
+    /// Fee the transaction pays, which orders the ready transactions.
+    pub fee: Balance,
+    pub encoded_size: usize,
+}
+
+/// Status of a watched transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionStatus<Hash> {
+    /// Waiting for a lower index of its sender.
+    Future,
+    /// Can be included in the next block.
+    Ready,
+    /// Included in the block of the given hash.
+    Included(Hash),
+    /// Replaced or dropped from the pool without being included.
+    Dropped,
+    /// Rejected by the pool, with the reason.
+    Invalid(String),
+}
@@ -0,0 +1,37 @@
+// Copyright 2018 Akropolis.
+
+use akro_api;
+use jsonrpc_core as rpccore;
+
+error_chain! {
+    links {
+        Api(akro_api::Error, akro_api::ErrorKind) #[doc = "Akro API error"];
+    }
+    errors {
+        /// The requested block is not known
+        UnknownBlock {
+            description("unknown block"),
+            display("Unknown block"),
+        }
+    }
+}
+
+impl From<Error> for rpccore::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error(ErrorKind::UnknownBlock, _) => rpccore::Error {
+                code: rpccore::ErrorCode::InvalidParams,
+                message: "Unknown block".into(),
+                data: None,
+            },
+            e => {
+                warn!("Unknown error: {:?}", e);
+                rpccore::Error {
+                    code: rpccore::ErrorCode::InternalError,
+                    message: "Unknown error occured".into(),
+                    data: Some(format!("{:?}", e).into()),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright 2018 Akropolis.
+
+//! Shared by the RPCs serving decoded views of runtime modules through `AkroApi`.
+
+use akro_api::AkroApi;
+use akro_primitives::{BlockId, Hash};
+use jsonrpc_macros::Trailing;
+
+mod error;
+
+pub use self::error::{Error, ErrorKind, Result};
+
+/// The given block, or the best block if not given.
+pub fn block_id<A: AkroApi>(api: &A, hash: Trailing<Hash>) -> Result<BlockId> {
+    if let Some(hash) = hash.into() {
+        return Ok(BlockId::hash(hash));
+    }
+    match api.block_number_to_hash(api.current_height()) {
+        Some(hash) => Ok(BlockId::hash(hash)),
+        None => Err(ErrorKind::UnknownBlock.into()),
+    }
+}
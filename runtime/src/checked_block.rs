@@ -3,11 +3,21 @@
 //! Typesafe block interaction.
 
 use super::{
-    AccountId, Block, Call, BLOCK_PRODUCER_POSITION, NOTE_OFFLINE_POSITION, TIMESTAMP_SET_POSITION,
+    AccountId, Block, Call, ConsensusCall, BLOCK_PRODUCER_POSITION, NOTE_OFFLINE_POSITION,
+    TIMESTAMP_SET_POSITION,
 };
-use timestamp::Call as TimestampCall;
-//use session::Call as SessionCall;
 use arml_system::Call as CXSystemCall;
+use timestamp::Call as TimestampCall;
+
+/// The position an inherent extrinsic must be at, `None` for other calls.
+fn inherent_position(call: &Call) -> Option<u32> {
+    match *call {
+        Call::Timestamp(TimestampCall::set(_)) => Some(TIMESTAMP_SET_POSITION),
+        Call::CXSystem(CXSystemCall::set_block_producer(_)) => Some(BLOCK_PRODUCER_POSITION),
+        Call::Consensus(ConsensusCall::note_offline(_)) => Some(NOTE_OFFLINE_POSITION),
+        _ => None,
+    }
+}
 
 /// Provides a type-safe wrapper around a structurally valid block.
 pub struct CheckedBlock {
@@ -33,6 +43,32 @@ impl CheckedBlock {
             return Err(block);
         }
 
+        let has_block_producer = block
+            .extrinsics
+            .get(BLOCK_PRODUCER_POSITION as usize)
+            .map_or(false, |xt| {
+                !xt.is_signed()
+                    && match xt.function {
+                        Call::CXSystem(CXSystemCall::set_block_producer(_)) => true,
+                        _ => false,
+                    }
+            });
+
+        if !has_block_producer {
+            return Err(block);
+        }
+
+        // every inherent must be unsigned and at its own position, which also rules out
+        // duplicates.
+        let misplaced_inherent = block.extrinsics.iter().enumerate().any(|(i, xt)| {
+            inherent_position(&xt.function)
+                .map_or(false, |position| xt.is_signed() || i != position as usize)
+        });
+
+        if misplaced_inherent {
+            return Err(block);
+        }
+
         Ok(CheckedBlock {
             inner: block,
             file_line: None,
@@ -71,12 +107,13 @@ impl CheckedBlock {
             .extrinsics
             .get(NOTE_OFFLINE_POSITION as usize)
             .and_then(|xt| match xt.function {
-                //Call::Session(SessionCall::note_offline(ref x)) => Some(&x[..]),
+                Call::Consensus(ConsensusCall::note_offline(ref x)) => Some(&x[..]),
                 _ => None,
             })
             .unwrap_or(&[])
     }
 
+    /// Extract the block producer from the block.
     pub fn block_producer(&self) -> Option<AccountId> {
         self.inner
             .extrinsics
@@ -109,3 +146,167 @@ macro_rules! assert_akro_block {
         $crate::CheckedBlock::new_unchecked($block, file!(), line!())
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Header, UncheckedExtrinsic};
+
+    fn block_with(extrinsics: Vec<Call>) -> Block {
+        Block {
+            header: Header {
+                parent_hash: Default::default(),
+                number: 1,
+                state_root: Default::default(),
+                extrinsics_root: Default::default(),
+                digest: Default::default(),
+            },
+            extrinsics: extrinsics
+                .into_iter()
+                .map(UncheckedExtrinsic::new_unsigned)
+                .collect(),
+        }
+    }
+
+    fn set_timestamp() -> Call {
+        Call::Timestamp(TimestampCall::set(100))
+    }
+
+    fn set_block_producer(producer: u8) -> Call {
+        Call::CXSystem(CXSystemCall::set_block_producer([producer; 32].into()))
+    }
+
+    #[test]
+    fn block_with_producer_should_be_checked() {
+        let block = CheckedBlock::new(block_with(vec![set_timestamp(), set_block_producer(1)]))
+            .ok()
+            .unwrap();
+        assert_eq!(block.timestamp(), 100);
+        assert_eq!(block.block_producer(), Some([1; 32].into()));
+    }
+
+    #[test]
+    fn block_without_producer_should_fail() {
+        assert!(CheckedBlock::new(block_with(vec![set_timestamp()])).is_err());
+        assert!(CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            Call::Consensus(ConsensusCall::note_offline(vec![0])),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn block_producer_out_of_position_should_fail() {
+        assert!(
+            CheckedBlock::new(block_with(vec![set_block_producer(1), set_timestamp()])).is_err()
+        );
+        assert!(CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            Call::Consensus(ConsensusCall::note_offline(vec![0])),
+            set_block_producer(1),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn inherent_extrinsics_should_be_checked() {
+        use InherentData;
+
+        let extrinsics = ::inherent_extrinsics(InherentData {
+            timestamp: 100,
+            offline_indices: vec![],
+            block_producer: [2; 32].into(),
+        });
+        let block = Block {
+            extrinsics,
+            ..block_with(vec![])
+        };
+        let block = CheckedBlock::new(block).ok().unwrap();
+        assert_eq!(block.block_producer(), Some([2; 32].into()));
+    }
+
+    fn note_offline(indices: Vec<u32>) -> Call {
+        Call::Consensus(ConsensusCall::note_offline(indices))
+    }
+
+    fn remark() -> Call {
+        Call::Consensus(ConsensusCall::remark(vec![]))
+    }
+
+    #[test]
+    fn noted_offline_should_be_decoded() {
+        let block = CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            set_block_producer(1),
+            note_offline(vec![1, 3]),
+            remark(),
+        ]))
+        .ok()
+        .unwrap();
+        assert_eq!(block.noted_offline(), &[1, 3]);
+
+        let block = CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            set_block_producer(1),
+            remark(),
+        ]))
+        .ok()
+        .unwrap();
+        assert_eq!(block.noted_offline(), &[] as &[u32]);
+    }
+
+    #[test]
+    fn misplaced_note_offline_should_fail() {
+        assert!(CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            set_block_producer(1),
+            remark(),
+            note_offline(vec![0]),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn duplicated_inherents_should_fail() {
+        assert!(CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            set_block_producer(1),
+            note_offline(vec![0]),
+            set_timestamp(),
+        ]))
+        .is_err());
+        assert!(CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            set_block_producer(1),
+            remark(),
+            set_block_producer(2),
+        ]))
+        .is_err());
+        assert!(CheckedBlock::new(block_with(vec![
+            set_timestamp(),
+            set_block_producer(1),
+            note_offline(vec![0]),
+            note_offline(vec![1]),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn inherent_extrinsics_with_offline_should_be_checked() {
+        use InherentData;
+
+        let extrinsics = ::inherent_extrinsics(InherentData {
+            timestamp: 100,
+            offline_indices: vec![2],
+            block_producer: [2; 32].into(),
+        });
+        let block = Block {
+            extrinsics,
+            ..block_with(vec![])
+        };
+        let block = CheckedBlock::new(block).ok().unwrap();
+        assert_eq!(block.timestamp(), 100);
+        assert_eq!(block.block_producer(), Some([2; 32].into()));
+        assert_eq!(block.noted_offline(), &[2]);
+    }
+}
@@ -40,6 +40,13 @@ extern crate substrate_primitives;
 extern crate sr_version as version;
 extern crate akro_primitives;
 
+// akro runtime module
+extern crate arml_support;
+extern crate arml_system;
+// mining
+extern crate arml_mining_staking as staking;
+extern crate arml_mining_tokenstaking as tokenstaking;
+
 #[cfg(feature = "std")]
 mod checked_block;
 
@@ -47,15 +54,19 @@ pub use balances::address::Address as RawAddress;
 #[cfg(feature = "std")]
 pub use checked_block::CheckedBlock;
 pub use runtime_primitives::{Perbill, Permill};
-pub use tokenbalances::Token;
+pub use staking::{CertProfs, EraInfo, IntentionProfs, NominationRecord};
+pub use tokenbalances::{Symbol, Token};
+pub use tokenstaking::{HodlingRecord, SessionStake, VirtualIntentionProfs};
 
 use akro_primitives::InherentData;
 use akro_primitives::{
     AccountId, AccountIndex, Balance, BlockNumber, Hash, Index, SessionKey, Signature,
 };
 pub use consensus::Call as ConsensusCall;
+use arml_support::CallFee;
+use codec::Encode;
 use council::{motions as council_motions, voting as council_voting};
-// use arml_system::Call as CXSystemCall;
+use arml_system::Call as CXSystemCall;
 use rstd::prelude::*;
 use runtime_primitives::generic;
 use runtime_primitives::traits::{BlakeTwo256, Convert, DigestItem};
@@ -78,9 +89,9 @@ pub fn inherent_extrinsics(data: InherentData) -> Vec<UncheckedExtrinsic> {
         Call::Timestamp(TimestampCall::set(data.timestamp)),
     )];
 
-    // inherent.push(generic::UncheckedMortalExtrinsic::new_unsigned(
-    //     Call::CXSystem(CXSystemCall::set_block_producer(data.block_producer)),
-    // ));
+    inherent.push(generic::UncheckedMortalExtrinsic::new_unsigned(
+        Call::CXSystem(CXSystemCall::set_block_producer(data.block_producer)),
+    ));
 
     if !data.offline_indices.is_empty() {
         inherent.push(generic::UncheckedMortalExtrinsic::new_unsigned(
@@ -102,6 +113,7 @@ pub const TIMESTAMP_SET_POSITION: u32 = 0;
 /// The position of the offline nodes noting extrinsic.
 pub const NOTE_OFFLINE_POSITION: u32 = 2;
 
+/// The position of the block producer set extrinsic.
 pub const BLOCK_PRODUCER_POSITION: u32 = 1;
 
 /// Runtime version.
@@ -204,6 +216,14 @@ impl council::motions::Trait for Runtime {
     type Event = Event;
 }
 
+impl arml_system::Trait for Runtime {
+    type OnBlockStart = (Staking, TokenStaking);
+}
+
+impl arml_support::Trait for Runtime {
+    type Event = Event;
+}
+
 impl tokenbalances::Trait for Runtime {
     const AKRO_SYMBOL: tokenbalances::SymbolString = b"PCX";
     const AKRO_TOKEN_DESC: tokenbalances::DescString = b"Polkadot Akro";
@@ -269,6 +289,8 @@ construct_runtime!(
         Treasury: treasury,
         Contract: contract::{Module, Call, Config, Event<T>},
         // akro runtime module
+        CXSystem: arml_system::{Module, Call, Storage, Config},
+        CXSupport: arml_support::{Module, Storage, Event<T>},
         TokenBalances: tokenbalances,
         // funds
         FinancialRecords: financialrecords::{Module, Call, Storage, Event<T>},
@@ -293,11 +315,50 @@ pub type UncheckedExtrinsic = generic::UncheckedMortalExtrinsic<Address, Index,
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Index, Call>;
 /// Executive: handles dispatch to the various modules.
 pub type Executive =
-    executive::Executive<Runtime, Block, balances::ChainContext<Runtime>, Balances, AllModules>;
+    executive::Executive<Runtime, Block, balances::ChainContext<Runtime>, CXSupport, AllModules>;
 
 // define tokenbalances module type
 pub type TokenBalance = u128;
 
+/// Free, reserved, staking-locked and frozen balance of an account.
+pub fn account_balances(who: &AccountId) -> (Balance, Balance, Balance, Balance) {
+    (
+        Balances::free_balance(who),
+        Balances::reserved_balance(who),
+        Staking::nominator_profiles(who).locked,
+        Staking::intention_profiles(who).frozen,
+    )
+}
+
+impl CallFee<Balance> for Call {
+    fn call_fee(&self) -> Balance {
+        match *self {
+            Call::Staking(ref call) => call.call_fee(),
+            Call::TokenStaking(ref call) => call.call_fee(),
+            _ => 0,
+        }
+    }
+}
+
+/// Fee an extrinsic pays, the transaction fee of its length and the fee of its call.
+pub fn extrinsic_fee(xt: &UncheckedExtrinsic) -> Balance {
+    let len = xt.encode().len() as Balance;
+    Balances::transaction_base_fee()
+        .saturating_add(Balances::transaction_byte_fee().saturating_mul(len))
+        .saturating_add(xt.function.call_fee())
+}
+
+/// Balance of an account in each valid token.
+pub fn token_balances_of(who: &AccountId) -> Vec<(Symbol, TokenBalance)> {
+    TokenBalances::valid_token_list()
+        .into_iter()
+        .map(|sym| {
+            let balance = TokenBalances::total_token_of(who, &sym);
+            (sym, balance)
+        })
+        .collect()
+}
+
 pub mod api {
     impl_stubs!(
         version => |()| super::VERSION,
@@ -311,10 +372,25 @@ pub mod api {
         validator_count => |()| super::Session::validator_count(),
         validators => |()| super::Session::validators(),
         stake_weight => |account| super::Staking::stake_weight(&account),
+        authored_blocks => |account| super::CXSystem::authored_blocks_of(&account),
+        staking_intentions => |()| super::Staking::intentions_with_profile(),
+        staking_intention => |account| super::Staking::intention_with_profile(&account),
+        staking_nomination_records => |account| super::Staking::nomination_records_with_dividend(&account),
+        staking_pending_dividend => |(nominator, intention)| super::Staking::pending_dividend(&nominator, &intention),
+        staking_certs => |()| super::Staking::certs(),
+        staking_unbonding => |account| super::Staking::unbonding(&account),
+        staking_era_info => |()| super::Staking::era_info(),
+        tokenstaking_tokens => |()| super::TokenStaking::staking_tokens(),
+        tokenstaking_hodling_records => |account| super::TokenStaking::hodling_records_with_dividend(&account),
+        tokenstaking_session_stakes => |()| super::TokenStaking::last_session_stakes(),
         timestamp => |()| super::Timestamp::get(),
         random_seed => |()| super::System::random_seed(),
         account_nonce => |account| super::System::account_nonce(&account),
+        death_account => |()| super::CXSystem::death_account(),
         lookup_address => |address| super::Balances::lookup_address(address),
+        account_balances => |account| super::account_balances(&account),
+        token_balances => |account| super::token_balances_of(&account),
+        extrinsic_fee => |xt| super::extrinsic_fee(&xt),
         verify_addr => |(sym, addr, ext)| super::Withdrawal::verify_address(sym, addr, ext)
     );
 }
@@ -1,7 +1,10 @@
 // Copyright 2018 Akropolis
 
+use akro_pool::PoolConfig;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub enum ChainSpec {
@@ -68,6 +71,67 @@ pub fn build_cli() -> App<'static, 'static> {
                 .help("Specify the database directory path")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("indexer")
+                .long("indexer")
+                .help("Index the extrinsics each account sent or received, for chainext_accountHistory")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("pool-max-count")
+                .long("pool-max-count")
+                .value_name("COUNT")
+                .help("Maximum number of transactions in the transaction pool")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pool-max-mem")
+                .long("pool-max-mem")
+                .value_name("MB")
+                .help("Maximum memory used by the transactions in the transaction pool, in megabytes")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pool-max-per-sender")
+                .long("pool-max-per-sender")
+                .value_name("COUNT")
+                .help("Maximum number of transactions of a single sender in the transaction pool")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pool-max-future")
+                .long("pool-max-future")
+                .value_name("COUNT")
+                .help("Maximum number of indices a pooled transaction may be ahead of its sender's next index")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pool-fee-bump")
+                .long("pool-fee-bump")
+                .value_name("PERCENT")
+                .help("Percentage by which a transaction must out-pay a pooled one of the same index to replace it")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pool-ban-time")
+                .long("pool-ban-time")
+                .value_name("SECONDS")
+                .help("How long transactions rejected as invalid are refused by the transaction pool")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pool-journal")
+                .long("pool-journal")
+                .help("Keep the ready transactions across restarts, the default for validators")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("no-pool-journal")
+                .long("no-pool-journal")
+                .help("Do not keep the ready transactions across restarts")
+                .conflicts_with("pool-journal")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("key")
                 .long("key")
@@ -145,3 +209,37 @@ pub fn parse_address(
 
     Ok(address)
 }
+
+fn parse_value<T: FromStr>(param: &str, matches: &ArgMatches) -> Result<Option<T>, String> {
+    match matches.value_of(param) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid value for --{} specified.", param)),
+        None => Ok(None),
+    }
+}
+
+/// Transaction pool configuration, the defaults overridden by the `--pool-*` flags.
+pub fn parse_pool_config(matches: &ArgMatches) -> Result<PoolConfig, String> {
+    let mut config = PoolConfig::default();
+    if let Some(count) = parse_value("pool-max-count", matches)? {
+        config.max_count = count;
+    }
+    if let Some(mb) = parse_value::<usize>("pool-max-mem", matches)? {
+        config.max_mem_usage = mb * 1024 * 1024;
+    }
+    if let Some(count) = parse_value("pool-max-per-sender", matches)? {
+        config.max_per_sender = count;
+    }
+    if let Some(count) = parse_value("pool-max-future", matches)? {
+        config.max_future = count;
+    }
+    if let Some(percent) = parse_value("pool-fee-bump", matches)? {
+        config.fee_bump = percent;
+    }
+    if let Some(secs) = parse_value("pool-ban-time", matches)? {
+        config.ban_time = Duration::from_secs(secs);
+    }
+    Ok(config)
+}
@@ -4,7 +4,7 @@ extern crate substrate_primitives;
 
 use self::base58::FromBase58;
 use akro_runtime::{
-    BalancesConfig, BalancesConfigCopy, ConsensusConfig, ContractConfig, CouncilVotingConfig, DemocracyConfig,
+    BalancesConfig, BalancesConfigCopy, CXSystemConfig, ConsensusConfig, ContractConfig, CouncilVotingConfig, DemocracyConfig,
     GenesisConfig, Params, Perbill, Permill, Runtime, SessionConfig, StakingConfig, 
     TimestampConfig, Token, TokenBalancesConfig, TokenStakingConfig, TreasuryConfig, 
     };
@@ -110,6 +110,10 @@ pub fn testnet_genesis(chainspec: ChainSpec) -> GenesisConfig {
             nominate_fee: 1,
             unnominate_fee: 1,
         }),
+        arml_system: Some(CXSystemConfig {
+            death_account: [0u8; 32].into(),
+            fee_buy_account: auth1.0.into(),
+        }),
         tokenstaking: Some(TokenStakingConfig {
             fee: 10
         }),
@@ -17,6 +17,7 @@ extern crate substrate_state_machine as state_machine;
 extern crate akro_api;
 extern crate akro_consensus as consensus;
 extern crate akro_executor;
+extern crate akro_indexer;
 extern crate akro_network;
 extern crate akro_pool;
 extern crate akro_primitives;
@@ -52,18 +53,27 @@ use substrate_client::BlockchainEvents;
 use substrate_primitives::{ed25519, ed25519::Pair, storage::StorageKey, twox_128};
 
 use akro_api::TClient;
+use akro_indexer::{AccountHistory, Indexer};
 use akro_network::consensus::ConsensusNetwork;
-use akro_pool::{Pool, PoolApi, TransactionPool};
+use akro_pool::{Journal, Pool, PoolApi, TransactionPool};
 use akro_primitives::{Block, BlockId, Hash, Timestamp};
 use akro_runtime::{BlockPeriod, Runtime as akroRuntime, StorageValue};
 use cli::ChainSpec;
 
 use codec::Decode;
 use names::{Generator, Name};
-use std::sync::Arc;
+use std::path::Path;
+use std::process;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use tokio::prelude::Future;
 use tokio::prelude::Stream;
 use tokio::runtime::Runtime;
+use tokio::timer::Interval;
+
+/// How often the transaction pool journal is saved.
+const POOL_JOURNAL_INTERVAL_SECS: u64 = 60;
 
 fn main() {
     let _ = env_logger::try_init();
@@ -103,6 +113,13 @@ fn main() {
             .unwrap(),
         None => 20222,
     };
+    let pool_config = match cli::parse_pool_config(&matches) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
     let mut boot_nodes: Vec<String> = Vec::new();
     boot_nodes.extend(
         matches
@@ -116,16 +133,53 @@ fn main() {
     let client = client::build_client(db_path, chainspec, pruning);
     
     let (exit_send, exit) = exit_future::signal();
+    let exit_send = Mutex::new(Some(exit_send));
+    ctrlc::set_handler(move || {
+        if let Some(exit_send) = exit_send.lock().unwrap().take() {
+            info!("Shutting down");
+            exit_send.fire();
+        }
+    })
+    .expect("failed to set the termination handler");
     let mut runtime = Runtime::new().expect("failed to start runtime on current thread");
     let task_executor = runtime.executor();
 
     let extrinsic_pool = Arc::new(TransactionPool::new(
-        Default::default(),
+        pool_config,
         PoolApi::new(client.clone() as Arc<TClient>),
         client.clone(),
     ));
 
     let validator_mode = matches.subcommand_matches("validator").is_some();
+
+    let pool_journal = if matches.is_present("pool-journal")
+        || (validator_mode && !matches.is_present("no-pool-journal"))
+    {
+        let journal = Arc::new(Journal::new(Path::new(db_path).join("pool_journal")));
+        if let Err(e) = journal.load(&extrinsic_pool) {
+            warn!("Error restoring the transaction pool journal: {:?}", e);
+        }
+
+        let txpool = extrinsic_pool.clone();
+        let periodic = {
+            let journal = journal.clone();
+            Interval::new(
+                Instant::now() + Duration::from_secs(POOL_JOURNAL_INTERVAL_SECS),
+                Duration::from_secs(POOL_JOURNAL_INTERVAL_SECS),
+            )
+            .map_err(|e| debug!("Timer error: {:?}", e))
+            .for_each(move |_| {
+                if let Err(e) = journal.save(&txpool) {
+                    warn!("Error saving the transaction pool journal: {:?}", e);
+                }
+                Ok(())
+            })
+        };
+        task_executor.spawn(periodic.select(exit.clone()).then(|_| Ok(())));
+        Some(journal)
+    } else {
+        None
+    };
     let multi_address = matches.values_of("listen-addr").unwrap_or_default();
     let network = network::build_network(
         port,
@@ -147,7 +201,6 @@ fn main() {
             .for_each(move |notification| {
                 network.on_block_imported(notification.hash, &notification.header);
                 txpool
-                    .inner()
                     .cull(&BlockId::hash(notification.hash))
                     .map_err(|e| warn!("Error removing extrinsics: {:?}", e))?;
                 Ok(())
@@ -175,6 +228,47 @@ fn main() {
         task_executor.spawn(events);
     }
 
+    let account_history = if matches.is_present("indexer") {
+        let account_history = Arc::new(
+            AccountHistory::open(&Path::new(db_path).join("indexer"))
+                .expect("failed to open the account indexer database"),
+        );
+        let indexer = Indexer::new(client.clone(), account_history.clone());
+        info!("Account indexer is enabled");
+
+        // catching up can take long, so the indexer runs on a thread of its own: it catches up
+        // with the best block, then follows the new best blocks
+        let (best_sender, best_receiver) = mpsc::channel();
+        best_sender
+            .send(client.info().unwrap().chain.best_hash)
+            .expect("the receiver is alive; qed");
+        thread::Builder::new()
+            .name("account-indexer".into())
+            .spawn(move || {
+                while let Ok(best) = best_receiver.recv() {
+                    // only the latest of the queued best blocks matters
+                    let best = best_receiver.try_iter().last().unwrap_or(best);
+                    if let Err(e) = indexer.sync(best) {
+                        warn!("Error indexing accounts: {:?}", e);
+                    }
+                }
+            })
+            .expect("failed to spawn the account indexer thread");
+
+        let follow = client
+            .import_notification_stream()
+            .filter(|notification| notification.is_new_best)
+            .for_each(move |notification| {
+                // the indexer thread stops once this stream is dropped on exit
+                let _ = best_sender.send(notification.hash);
+                Ok(())
+            });
+        task_executor.spawn(follow.select(exit.clone()).then(|_| Ok(())));
+        Some(account_history)
+    } else {
+        None
+    };
+
     let _consensus = if validator_mode {
         let mut key = match matches
             .subcommand_matches("validator")
@@ -254,7 +348,13 @@ fn main() {
         None
     };
 
-    let (_rpc_http, _rpc_ws) = rpc::start(&client, &task_executor, &matches, &extrinsic_pool);
+    let (_rpc_http, _rpc_ws) = rpc::start(
+        &client,
+        &task_executor,
+        &matches,
+        &extrinsic_pool,
+        account_history,
+    );
 
     if matches.is_present("telemetry") {
         let telemetry_url = match matches.value_of("telemetry-url") {
@@ -272,5 +372,10 @@ fn main() {
         let _ = runtime.block_on(exit);
     }
 
-    exit_send.fire();
+    if let Some(journal) = pool_journal {
+        match journal.save(&extrinsic_pool) {
+            Ok(count) => info!("Saved {} transactions to the pool journal", count),
+            Err(e) => warn!("Error saving the transaction pool journal: {:?}", e),
+        }
+    }
 }
@@ -2,6 +2,7 @@
 
 use akro_api::AkroApi;
 use akro_api::TClient;
+use akro_indexer::AccountHistory;
 use akro_pool::TransactionPool;
 use akro_primitives;
 use akro_rpc;
@@ -19,6 +20,7 @@ pub fn start<A>(
     task_executor: &TaskExecutor,
     matches: &clap::ArgMatches,
     extrinsic_pool: &Arc<TransactionPool<A>>,
+    account_history: Option<Arc<AccountHistory>>,
 ) -> (
     Result<Option<HttpServer>, io::Error>,
     Result<Option<WsServer>, io::Error>,
@@ -26,11 +28,24 @@ pub fn start<A>(
 where
     A: AkroApi + Send + Sync + 'static,
 {
-    let handler = || {
+    // methods changing the node, like `pool_remove`, are only served on local interfaces
+    let handler = |allow_unsafe: bool| {
         let subscriptions = rpc_server::apis::Subscriptions::new(task_executor.clone());
 
         let chain = rpc_server::apis::chain::Chain::new(client.clone(), subscriptions.clone());
-        let chain_ext = akro_rpc::chainext::ChainExt::new(client.clone(), task_executor.clone());
+        let mut chain_ext =
+            akro_rpc::chainext::ChainExt::new(client.clone(), task_executor.clone());
+        if let Some(ref account_history) = account_history {
+            chain_ext = chain_ext.with_account_history(account_history.clone());
+        }
+        let staking = akro_rpc::staking::Staking::new(client.clone());
+        let tokenstaking = akro_rpc::tokenstaking::TokenStaking::new(client.clone());
+        let account = akro_rpc::account::Account::new(client.clone(), extrinsic_pool.clone());
+        let txpool = akro_rpc::txpool::TxPool::new(
+            extrinsic_pool.clone(),
+            task_executor.clone(),
+            allow_unsafe,
+        );
         let state = rpc_server::apis::state::State::new(client.clone(), subscriptions.clone());
         let author = rpc_server::apis::author::Author::new(
             client.clone(),
@@ -46,24 +61,26 @@ where
             _,
             _,
             _,
+            _,
+            _,
+            _,
+            _,
         >(
             state,
             chain,
             chain_ext,
+            staking,
+            tokenstaking,
+            account,
+            txpool,
             author,
             akro_rpc::default_rpc_config(),
         )
     };
-    let rpc_interface: &str = if matches.is_present("rpc-external") {
-        "0.0.0.0"
-    } else {
-        "127.0.0.1"
-    };
-    let ws_interface: &str = if matches.is_present("ws-external") {
-        "0.0.0.0"
-    } else {
-        "127.0.0.1"
-    };
+    let rpc_external = matches.is_present("rpc-external");
+    let ws_external = matches.is_present("ws-external");
+    let rpc_interface: &str = if rpc_external { "0.0.0.0" } else { "127.0.0.1" };
+    let ws_interface: &str = if ws_external { "0.0.0.0" } else { "127.0.0.1" };
     let rpc_http_addr = Some(
         cli::parse_address(&format!("{}:{}", rpc_interface, 8081), "rpc-port", &matches).unwrap(),
     );
@@ -73,12 +90,12 @@ where
 
     let rpc_http: Result<Option<HttpServer>, io::Error> =
         akro_rpc::maybe_start_server(rpc_http_addr, |address| {
-            akro_rpc::servers::start_http(address, handler())
+            akro_rpc::servers::start_http(address, handler(!rpc_external))
         });
 
     let rpc_ws: Result<Option<WsServer>, io::Error> =
         akro_rpc::maybe_start_server(rpc_ws_addr, |address| {
-            akro_rpc::servers::start_ws(address, handler())
+            akro_rpc::servers::start_ws(address, handler(!ws_external))
         });
 
     (rpc_http, rpc_ws)